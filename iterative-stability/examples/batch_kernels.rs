@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+use iterative_stability::batch::{self, Kernel};
+use iterative_stability::formula::Mandelbrot;
+use iterative_stability::viewport::Viewport;
+use iterative_stability::{RenderParams, Termination};
+use rayon::prelude::*;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 800;
+const RUNS: u32 = 5;
+
+fn main() {
+    // interior checks are disabled so that every kernel iterates the whole set
+    let params = RenderParams {
+        interior_checks: false,
+        ..RenderParams::default()
+    };
+    let viewport = Viewport::from_bounds((-2.0, 0.5), (-1.25, 1.25), (WIDTH, HEIGHT));
+    let kernels = [Kernel::Scalar, Kernel::Lanes, Kernel::Avx];
+
+    let mut scalar = None;
+    for &kernel in &kernels {
+        if !kernel.is_supported() {
+            println!("{:?}: not supported by this CPU", kernel);
+            continue;
+        }
+        let (escaped, time) = best_of(|| {
+            batch::calc_screen_space(Mandelbrot, viewport, kernel, params)
+                .filter(|result| result.termination == Termination::Escaped)
+                .count()
+        });
+        let speedup = scalar.get_or_insert(time).as_secs_f64() / time.as_secs_f64();
+        println!(
+            "{:?}: {:.1} ms, {:.2}x scalar ({} pixels escaped)",
+            kernel,
+            time.as_secs_f64() * 1e3,
+            speedup,
+            escaped
+        );
+    }
+}
+
+fn best_of<T>(run: impl Fn() -> T) -> (T, Duration) {
+    let mut best = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let output = run();
+        let time = start.elapsed();
+        match best {
+            Some((_, best_time)) if best_time <= time => {}
+            _ => best = Some((output, time)),
+        }
+    }
+    best.unwrap()
+}
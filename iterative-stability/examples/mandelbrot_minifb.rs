@@ -1,11 +1,16 @@
-use iterative_stability::mandelbrot;
+use iterative_stability::double_double::DoubleDouble;
+use iterative_stability::interior::InteriorEstimate;
+use iterative_stability::viewport::{Aspect, Viewport};
+use iterative_stability::{check_precision, mandelbrot, RenderParams};
 use minifb::{Key, Window, WindowOptions};
+use num_complex::Complex;
+use num_traits::Float;
 use palette::{Hsv, Hue, Srgb};
-use rayon::prelude::*;
 
 const WIDTH: usize = 1200;
 const HEIGHT: usize = 1200;
-const ZOOM_FACTOR: f64 = 0.25;
+const ZOOM: f64 = 2.0;
+const ITERATIONS_PER_ZOOM: u64 = 250;
 
 fn main() {
     let mut window = Window::new("Mandelbrot", WIDTH, HEIGHT, WindowOptions::default())
@@ -16,22 +21,31 @@ fn main() {
     // Limit to max ~30 fps update rate
     window.limit_update_rate(Some(std::time::Duration::from_micros(33333)));
 
+    let mut buffer = vec![0; WIDTH * HEIGHT];
     let mut buffer_needs_update = true;
-    let mut x_bounds = (-2.5, 1.5);
-    let mut y_bounds = (-2.0, 2.0);
-    let mut scale = (x_bounds.1 - x_bounds.0, y_bounds.1 - y_bounds.0);
-    let mut delta_x = scale.0 / WIDTH as f64;
-    let mut delta_y = scale.1 / HEIGHT as f64;
-    let mut offset = (
-        (x_bounds.0 + x_bounds.1) / 2.0,
-        (y_bounds.0 + y_bounds.1) / 2.0,
+    let mut max_iterations = RenderParams::<f64>::default().max_iterations;
+    let dd = DoubleDouble::from;
+    // the view is kept in double-double, f64 is used for rendering as long as it is precise enough
+    let mut viewport = Viewport::from_bounds_with(
+        (dd(-2.5), dd(1.5)),
+        (dd(-2.0), dd(2.0)),
+        (WIDTH as u32, HEIGHT as u32),
+        Aspect::Fit,
     );
     while window.is_open() && !window.is_key_down(Key::Escape) {
         if buffer_needs_update {
-            let buffer: Vec<u32> =
-                mandelbrot::calc_screen_space(x_bounds, y_bounds, (WIDTH as i32, HEIGHT as i32))
-                    .map(|(iter, stable)| apply_palette(iter, stable))
-                    .collect();
+            let narrow = Viewport::new(
+                Complex::new(viewport.center().re.hi(), viewport.center().im.hi()),
+                viewport.width().hi(),
+                viewport.resolution(),
+            );
+            match check_precision(narrow) {
+                Ok(()) => render(&mut buffer, narrow, max_iterations),
+                Err(error) => {
+                    println!("{}, rendering in double-double precision", error);
+                    render(&mut buffer, viewport, max_iterations)
+                }
+            }
 
             // We unwrap here as we want this code to exit if it fails. Real applications may want to handle this in a different way
             window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
@@ -39,47 +53,67 @@ fn main() {
         } else {
             window.update();
             let mouse = window.get_mouse_pos(minifb::MouseMode::Discard);
-            match mouse {
-                Some(m) => {
-                    if window.get_mouse_down(minifb::MouseButton::Left) {
-                        let x = ((m.0 as f64 - (WIDTH as f64 / 2.0)) * delta_x) + offset.0;
-                        let y = ((-m.1 as f64 + (HEIGHT as f64 / 2.0)) * delta_y) + offset.1;
-                        x_bounds = (
-                            (x as f64 - (scale.0 / 2.0)) + (ZOOM_FACTOR * scale.0),
-                            (x as f64 + (scale.0 / 2.0)) - (ZOOM_FACTOR * scale.0),
-                        );
-                        y_bounds = (
-                            (y as f64 - (scale.1 / 2.0)) + (ZOOM_FACTOR * scale.1),
-                            (y as f64 + (scale.1 / 2.0)) - (ZOOM_FACTOR * scale.1),
-                        );
-                        scale = (x_bounds.1 - x_bounds.0, y_bounds.1 - y_bounds.0);
-                        delta_x = scale.0 / WIDTH as f64;
-                        delta_y = scale.1 / HEIGHT as f64;
-                        offset = (
-                            (x_bounds.0 + x_bounds.1) / 2.0,
-                            (y_bounds.0 + y_bounds.1) / 2.0,
-                        );
-                        println!("zooming {:?} {:?}", x_bounds, y_bounds);
-                        buffer_needs_update = true;
+            if let Some(m) = mouse {
+                if window.get_mouse_down(minifb::MouseButton::Left) {
+                    // center the clicked point and magnify around it
+                    let clicked = (
+                        dd(m.0 as f64) - dd(WIDTH as f64 / 2.0),
+                        dd(m.1 as f64) - dd(HEIGHT as f64 / 2.0),
+                    );
+                    let center = (dd(WIDTH as f64 / 2.0), dd(HEIGHT as f64 / 2.0));
+                    let zoomed = viewport.pan(clicked).zoom(center, dd(ZOOM));
+                    if let Err(error) = check_precision(zoomed) {
+                        println!("{}, not zooming any further", error);
+                        continue;
                     }
+                    viewport = zoomed;
+                    max_iterations += ITERATIONS_PER_ZOOM;
+                    println!(
+                        "zooming to {} width {}, max iterations {}",
+                        viewport.center(),
+                        viewport.width(),
+                        max_iterations
+                    );
+                    buffer_needs_update = true;
                 }
-                None => {}
             }
         }
     }
 }
 
-pub fn apply_palette(iter: u64, stable: bool) -> u32 {
-    if stable {
-        0
+fn render<F>(buffer: &mut [u32], viewport: Viewport<F>, max_iterations: u64)
+where
+    F: Float + Send + Sync,
+{
+    let params = RenderParams {
+        max_iterations,
+        ..RenderParams::default()
+    };
+    mandelbrot::render_into_interior(buffer, WIDTH, viewport, params, |estimate| {
+        apply_palette(estimate, params.bailout)
+    });
+}
+
+pub fn apply_palette<F>(estimate: InteriorEstimate<F>, bailout: F) -> u32
+where
+    F: Float,
+{
+    let degree = F::from(2).unwrap();
+    let new_color: Srgb = if let Some(iter) = estimate.result.smooth_iterations(bailout, degree) {
+        Hsv::new(0.0, 1.0, 1.0)
+            .shift_hue(iter.to_f32().unwrap() * 0.7)
+            .into()
+    } else if let Some(cycle) = estimate.cycle {
+        // hue by period, darker towards the superattracting center
+        let value = cycle.multiplier.norm().to_f32().unwrap() * 0.5;
+        Hsv::new(cycle.period as f32 * 37.0, 0.6, value).into()
     } else {
-        let hsv_color = Hsv::new(0.0, 1.0, 1.0);
-        let new_color: Srgb = hsv_color.shift_hue((iter as f32 * 0.7) as f32).into();
-        u32::from_be_bytes([
-            0xff,
-            (new_color.red * 255.0) as u8,
-            (new_color.green * 255.0) as u8,
-            (new_color.blue * 255.0) as u8,
-        ])
-    }
+        Hsv::new(0.0, 0.0, 0.0).into()
+    };
+    u32::from_be_bytes([
+        0xff,
+        (new_color.red * 255.0) as u8,
+        (new_color.green * 255.0) as u8,
+        (new_color.blue * 255.0) as u8,
+    ])
 }
@@ -0,0 +1,345 @@
+use num_complex::Complex;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::formula::Formula;
+use crate::viewport::Viewport;
+use crate::{from_screen_point_to_cartesian, iterate, pixel_count, IterationResult, RenderParams};
+
+/// Per-pixel statistic gathered along the orbit while it is iterated.
+pub trait Accumulator<F>
+where
+    F: Float,
+{
+    type State: Copy;
+    type Output;
+
+    /// State before the first iteration, `z` being the initial orbit value and `c` the
+    /// [`Formula::addend`] of the rendered formula for the orbit.
+    fn init(&self, z: Complex<F>, c: Option<Complex<F>>) -> Self::State;
+
+    /// Folds the next orbit value `z` into the state.
+    fn accumulate(&self, state: Self::State, z: Complex<F>, c: Option<Complex<F>>) -> Self::State;
+
+    /// Turns the final state into the output. `smooth` is the normalized iteration count of
+    /// escaped orbits, used to interpolate between the last two iterations.
+    fn finish(
+        &self,
+        state: Self::State,
+        result: &IterationResult<Complex<F>>,
+        smooth: Option<F>,
+    ) -> Self::Output;
+}
+
+impl<F> Accumulator<F> for ()
+where
+    F: Float,
+{
+    type State = ();
+    type Output = ();
+
+    fn init(&self, _z: Complex<F>, _c: Option<Complex<F>>) {}
+
+    fn accumulate(&self, _state: (), _z: Complex<F>, _c: Option<Complex<F>>) {}
+
+    fn finish(&self, _state: (), _result: &IterationResult<Complex<F>>, _smooth: Option<F>) {}
+}
+
+/// Escape data of a single pixel together with the output of an [`Accumulator`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Accumulated<F, O> {
+    pub value: O,
+    pub result: IterationResult<Complex<F>>,
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F, T, A>(
+    formula: T,
+    accumulator: A,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = Accumulated<F, A::Output>>
+where
+    F: Float,
+    T: Formula<F>,
+    A: Accumulator<F>,
+{
+    (0..pixel_count(viewport.resolution()))
+        .map(move |index| from_screen_pixel(&formula, &accumulator, index, viewport, params))
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F, T, A>(
+    formula: T,
+    accumulator: A,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = Accumulated<F, A::Output>>
+where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+    A: Accumulator<F> + Send + Sync,
+    A::Output: Send,
+{
+    (0..pixel_count(viewport.resolution()))
+        .into_par_iter()
+        .map(move |index| from_screen_pixel(&formula, &accumulator, index, viewport, params))
+}
+
+fn from_screen_pixel<F, T, A>(
+    formula: &T,
+    accumulator: &A,
+    index: u64,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> Accumulated<F, A::Output>
+where
+    F: Float,
+    T: Formula<F>,
+    A: Accumulator<F>,
+{
+    let (x, y) = from_screen_point_to_cartesian(index, viewport);
+    let (result, state) = iterate(formula, accumulator, Complex::new(x, y), params);
+    let smooth = result.smooth_iterations(params.bailout, formula.degree());
+
+    Accumulated {
+        value: accumulator.finish(state, &result, smooth),
+        result,
+    }
+}
+
+/// Running average of per-iteration values, remembering the last one so that the average can
+/// be interpolated between the last two iterations.
+#[derive(Copy, Clone, Debug)]
+pub struct Average<F> {
+    sum: F,
+    last: F,
+    count: u64,
+}
+
+impl<F> Average<F>
+where
+    F: Float,
+{
+    fn new() -> Self {
+        Average {
+            sum: F::zero(),
+            last: F::zero(),
+            count: 0,
+        }
+    }
+
+    fn push(self, value: F) -> Self {
+        Average {
+            sum: self.sum + value,
+            last: value,
+            count: self.count + 1,
+        }
+    }
+
+    fn finish(&self, result: &IterationResult<Complex<F>>, smooth: Option<F>) -> F {
+        if self.count == 0 {
+            return F::zero();
+        }
+        let average = self.sum / F::from(self.count).unwrap();
+        match smooth {
+            Some(smooth) if self.count > 1 => {
+                let previous = (self.sum - self.last) / F::from(self.count - 1).unwrap();
+                let fraction = smooth - F::from(result.iterations).unwrap();
+                previous + (average - previous) * fraction
+            }
+            _ => average,
+        }
+    }
+}
+
+/// Stripe average coloring, the mean of `(sin(density * arg z) + 1) / 2` along the orbit.
+#[derive(Copy, Clone, Debug)]
+pub struct StripeAverage<F> {
+    pub density: F,
+}
+
+impl<F> Accumulator<F> for StripeAverage<F>
+where
+    F: Float,
+{
+    type State = Average<F>;
+    type Output = F;
+
+    fn init(&self, _z: Complex<F>, _c: Option<Complex<F>>) -> Average<F> {
+        Average::new()
+    }
+
+    fn accumulate(&self, state: Average<F>, z: Complex<F>, _c: Option<Complex<F>>) -> Average<F> {
+        let half = F::from(0.5).unwrap();
+        state.push(half * (self.density * z.arg()).sin() + half)
+    }
+
+    fn finish(
+        &self,
+        state: Average<F>,
+        result: &IterationResult<Complex<F>>,
+        smooth: Option<F>,
+    ) -> F {
+        state.finish(result, smooth)
+    }
+}
+
+/// Triangle inequality average coloring of quadratic maps, bounding every orbit value with the
+/// [`Formula::addend`] of the rendered formula. Formulas without one give `0`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TriangleInequalityAverage;
+
+impl<F> Accumulator<F> for TriangleInequalityAverage
+where
+    F: Float,
+{
+    /// Running average and the previous orbit value.
+    type State = (Average<F>, Complex<F>);
+    type Output = F;
+
+    fn init(&self, z: Complex<F>, _c: Option<Complex<F>>) -> Self::State {
+        (Average::new(), z)
+    }
+
+    fn accumulate(&self, state: Self::State, z: Complex<F>, c: Option<Complex<F>>) -> Self::State {
+        let (average, previous) = state;
+        let c = match c {
+            Some(c) => c.norm(),
+            None => return (average, z),
+        };
+        let square = previous.norm_sqr();
+        let low = (square - c).abs();
+        let high = square + c;
+        if high - low > F::zero() {
+            (average.push((z.norm() - low) / (high - low)), z)
+        } else {
+            (average, z)
+        }
+    }
+
+    fn finish(
+        &self,
+        state: Self::State,
+        result: &IterationResult<Complex<F>>,
+        smooth: Option<F>,
+    ) -> F {
+        state.0.finish(result, smooth)
+    }
+}
+
+/// Curvature average coloring, the mean turning angle of the orbit scaled to `[0, 1]`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CurvatureAverage;
+
+impl<F> Accumulator<F> for CurvatureAverage
+where
+    F: Float,
+{
+    /// Running average and the two previous orbit values.
+    type State = (Average<F>, Complex<F>, Option<Complex<F>>);
+    type Output = F;
+
+    fn init(&self, z: Complex<F>, _c: Option<Complex<F>>) -> Self::State {
+        (Average::new(), z, None)
+    }
+
+    fn accumulate(&self, state: Self::State, z: Complex<F>, _c: Option<Complex<F>>) -> Self::State {
+        let (average, previous, before) = state;
+        let average = match before {
+            Some(before) if previous != before => {
+                let turn = ((z - previous) / (previous - before)).arg().abs();
+                average.push(turn / F::from(std::f64::consts::PI).unwrap())
+            }
+            _ => average,
+        };
+        (average, z, Some(previous))
+    }
+
+    fn finish(
+        &self,
+        state: Self::State,
+        result: &IterationResult<Complex<F>>,
+        smooth: Option<F>,
+    ) -> F {
+        state.0.finish(result, smooth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use super::{
+        calc_screen_space, Accumulator, CurvatureAverage, StripeAverage, TriangleInequalityAverage,
+    };
+    use crate::formula::{Formula, Julia, Mandelbrot};
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams, Termination};
+
+    fn render<T, A>(formula: T, accumulator: A, resolution: (u32, u32)) -> Vec<f64>
+    where
+        T: Formula<f64> + Send + Sync,
+        A: Accumulator<f64, Output = f64> + Send + Sync,
+    {
+        let params = RenderParams {
+            bailout: 100.0,
+            ..RenderParams::default()
+        };
+        calc_screen_space(
+            formula,
+            accumulator,
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), resolution),
+            params,
+        )
+        .map(|accumulated| accumulated.value)
+        .collect()
+    }
+
+    #[test]
+    fn averages_stay_in_unit_interval() {
+        let resolution = (16, 16);
+        let stripes = render(Mandelbrot, StripeAverage { density: 5.0 }, resolution);
+        let tia = render(Mandelbrot, TriangleInequalityAverage, resolution);
+        let curvature = render(Mandelbrot, CurvatureAverage, resolution);
+        for values in &[stripes, tia, curvature] {
+            assert!(values.iter().all(|v| (-1e-9..=1.0 + 1e-9).contains(v)));
+            assert!(values.iter().any(|&v| v > 0.0));
+        }
+    }
+
+    #[test]
+    fn triangle_inequality_average_uses_julia_constant() {
+        let julia = Julia {
+            c: Complex64::new(-0.8, 0.156),
+        };
+        let tia = render(julia, TriangleInequalityAverage, (32, 32));
+        assert!(tia.iter().all(|v| (-1e-9..=1.0 + 1e-9).contains(v)));
+        assert!(tia.iter().any(|&v| v > 0.0));
+    }
+
+    #[test]
+    fn average_interpolates_between_last_two_iterations() {
+        let stripes = StripeAverage { density: 1.0 };
+        let state = (0..3).fold(stripes.init(Complex64::new(0.0, 0.0), None), |state, k| {
+            let z = if k < 2 {
+                Complex64::new(1.0, 0.0)
+            } else {
+                Complex64::new(0.0, 1.0)
+            };
+            stripes.accumulate(state, z, None)
+        });
+        let result = IterationResult {
+            value: Complex64::new(0.0, 1.0),
+            iterations: 3,
+            termination: Termination::Escaped,
+        };
+        // per-iteration values 0.5, 0.5, 1.0
+        assert!((stripes.finish(state, &result, None) - 2.0 / 3.0).abs() < 1e-12);
+        assert!((stripes.finish(state, &result, Some(3.0)) - 0.5).abs() < 1e-12);
+        assert!((stripes.finish(state, &result, Some(3.5)) - 7.0 / 12.0).abs() < 1e-12);
+    }
+}
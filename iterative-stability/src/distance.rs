@@ -0,0 +1,149 @@
+use num_complex::Complex;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::formula::Differentiable;
+use crate::viewport::Viewport;
+use crate::{
+    from_screen_point_to_cartesian, is_stable_by, pixel_count, IterationResult, RenderParams,
+    Termination,
+};
+
+/// Escape data of a single pixel together with its exterior distance estimate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DistanceEstimate<F> {
+    /// Estimated distance from the pixel to the set boundary, `None` for stable pixels.
+    ///
+    /// The estimate gets more accurate with larger bailout radii.
+    pub distance: Option<F>,
+    pub result: IterationResult<Complex<F>>,
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = DistanceEstimate<F>>
+where
+    F: Float,
+    T: Differentiable<F>,
+{
+    (0..pixel_count(viewport.resolution()))
+        .map(move |index| from_screen_pixel(&formula, index, viewport, params))
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = DistanceEstimate<F>>
+where
+    F: Float + Send + Sync,
+    T: Differentiable<F> + Send + Sync,
+{
+    (0..pixel_count(viewport.resolution()))
+        .into_par_iter()
+        .map(move |index| from_screen_pixel(&formula, index, viewport, params))
+}
+
+fn from_screen_pixel<F, T>(
+    formula: &T,
+    index: u64,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> DistanceEstimate<F>
+where
+    F: Float,
+    T: Differentiable<F>,
+{
+    let (x, y) = from_screen_point_to_cartesian(index, viewport);
+    let point = Complex::new(x, y);
+
+    if params.interior_checks && formula.is_known_interior(point) {
+        return DistanceEstimate {
+            distance: None,
+            result: IterationResult {
+                value: formula.initial(point),
+                iterations: 0,
+                termination: Termination::KnownInterior,
+            },
+        };
+    }
+
+    let orbit = is_stable_by(
+        |(z, dz)| (formula.step(z, point), formula.step_derivative(z, dz)),
+        (formula.initial(point), formula.initial_derivative()),
+        |(z, _)| formula.is_bounded(z, &params),
+        params.max_iterations,
+        |(a, _), (b, _)| params.is_same_point(a, b),
+    );
+    let (z, dz) = orbit.value;
+    let result = orbit.with_value(z);
+
+    DistanceEstimate {
+        distance: if result.is_stable() {
+            None
+        } else {
+            Some(exterior_distance(z, dz))
+        },
+        result,
+    }
+}
+
+/// `|z| ln|z| / (2 |dz|)`, the classic exterior distance estimate at the escaped orbit point `z`
+/// with derivative `dz`.
+fn exterior_distance<F>(z: Complex<F>, dz: Complex<F>) -> F
+where
+    F: Float,
+{
+    let radius = z.norm();
+    radius * radius.ln() / (dz.norm() * F::from(2).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use super::calc_screen_space;
+    use crate::formula::{Differentiable, Julia, Mandelbrot};
+    use crate::tests::single_pixel;
+    use crate::RenderParams;
+
+    fn params() -> RenderParams<f64> {
+        RenderParams {
+            bailout: 1e3,
+            ..RenderParams::default()
+        }
+    }
+
+    fn estimate<T>(formula: T, x: f64, y: f64) -> Option<f64>
+    where
+        T: Differentiable<f64> + Send + Sync,
+    {
+        calc_screen_space(formula, single_pixel(x, y), params()).collect::<Vec<_>>()[0].distance
+    }
+
+    #[test]
+    fn julia_distance_to_unit_circle() {
+        let julia = Julia {
+            c: Complex64::new(0.0, 0.0),
+        };
+        let distance = estimate(julia, 2.0, 0.0).unwrap();
+        assert!(distance > 0.25 && distance < 4.0, "{}", distance);
+        assert_eq!(estimate(julia, 0.5, 0.0), None);
+    }
+
+    #[test]
+    fn mandelbrot_distance_shrinks_towards_boundary() {
+        let far = estimate(Mandelbrot, 1.0, 0.0).unwrap();
+        let near = estimate(Mandelbrot, 0.3, 0.0).unwrap();
+        assert!(far > 0.75 / 4.0 && far < 0.75 * 4.0, "{}", far);
+        assert!(near < far);
+        assert!(near < 0.05 * 4.0);
+    }
+}
@@ -0,0 +1,1034 @@
+use std::cmp::Ordering;
+use std::f64::consts;
+use std::fmt;
+use std::num::FpCategory;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+use num_traits::{Float, Num, One, ToPrimitive, Zero};
+
+/// Unevaluated sum of two non-overlapping `f64`s, giving about 32 significant decimal digits.
+///
+/// Arithmetic, `sqrt`, `exp`, `ln` and the trigonometric functions are accurate to roughly
+/// double-double precision; functions derived from them (hyperbolic, inverse hyperbolic, `powf`)
+/// may lose some relative accuracy. The exponent range is that of `f64`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+const EPSILON: f64 = 4.930_380_657_631_324e-32;
+
+const PI: DoubleDouble = DoubleDouble {
+    hi: consts::PI,
+    lo: 1.224_646_799_147_353_2e-16,
+};
+const TAU: DoubleDouble = DoubleDouble {
+    hi: consts::TAU,
+    lo: 2.449_293_598_294_706_4e-16,
+};
+const FRAC_PI_2: DoubleDouble = DoubleDouble {
+    hi: consts::FRAC_PI_2,
+    lo: 6.123_233_995_736_766e-17,
+};
+const LN_2: DoubleDouble = DoubleDouble {
+    hi: consts::LN_2,
+    lo: 2.319_046_813_846_299_6e-17,
+};
+const LN_10: DoubleDouble = DoubleDouble {
+    hi: consts::LN_10,
+    lo: -2.170_756_223_382_249_4e-16,
+};
+
+/// `a + b` together with its rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// `a + b` together with its rounding error, requires `|a| >= |b|`.
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// `a * b` together with its rounding error, using the fused multiply-add of the target.
+#[cfg(target_feature = "fma")]
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+/// `a * b` together with its rounding error. Without a hardware fused multiply-add,
+/// `f64::mul_add` is emulated in software, so the error is computed from the halves of `a` and
+/// `b` (Dekker's product).
+#[cfg(not(target_feature = "fma"))]
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    let (a_hi, a_lo) = split(a);
+    let (b_hi, b_lo) = split(b);
+    let error = ((a_hi * b_hi - p) + a_hi * b_lo + a_lo * b_hi) + a_lo * b_lo;
+    (p, error)
+}
+
+/// Splits `a` into a high part of 26 significant bits and an exact low part (Veltkamp).
+#[cfg(not(target_feature = "fma"))]
+fn split(a: f64) -> (f64, f64) {
+    // 2^27 + 1
+    const SPLITTER: f64 = 134_217_729.0;
+    // above this, `SPLITTER * a` overflows, so `a` is split scaled down by 2^28
+    const THRESHOLD: f64 = 6.696_928_794_914_17e299;
+    const SCALE: f64 = 268_435_456.0;
+
+    if a.abs() > THRESHOLD {
+        let a = a / SCALE;
+        let t = SPLITTER * a;
+        let hi = t - (t - a);
+        (hi * SCALE, (a - hi) * SCALE)
+    } else {
+        let t = SPLITTER * a;
+        let hi = t - (t - a);
+        (hi, a - hi)
+    }
+}
+
+impl DoubleDouble {
+    /// Normalized sum `hi + lo`.
+    pub fn new(hi: f64, lo: f64) -> Self {
+        if !hi.is_finite() {
+            return DoubleDouble::from(hi);
+        }
+        let (hi, lo) = two_sum(hi, lo);
+        DoubleDouble { hi, lo }
+    }
+
+    /// Leading component, the nearest `f64` to the value.
+    pub fn hi(self) -> f64 {
+        self.hi
+    }
+
+    /// Trailing component.
+    pub fn lo(self) -> f64 {
+        self.lo
+    }
+
+    fn from_quick_sum(hi: f64, lo: f64) -> Self {
+        if !hi.is_finite() {
+            return DoubleDouble::from(hi);
+        }
+        let (hi, lo) = quick_two_sum(hi, lo);
+        DoubleDouble { hi, lo }
+    }
+
+    fn mul_f64(self, other: f64) -> Self {
+        let (p, e) = two_prod(self.hi, other);
+        DoubleDouble::from_quick_sum(p, e + self.lo * other)
+    }
+
+    /// Exact multiplication by `2^exponent`.
+    fn scale(self, exponent: i32) -> Self {
+        let half = exponent / 2;
+        let a = 2f64.powi(half);
+        let b = 2f64.powi(exponent - half);
+        DoubleDouble {
+            hi: self.hi * a * b,
+            lo: self.lo * a * b,
+        }
+    }
+
+    /// `exp(self) - 1` by Taylor series, for small arguments.
+    fn exp_m1_taylor(self) -> Self {
+        let mut term = self;
+        let mut sum = self;
+        for k in 2..40 {
+            term = term * self / DoubleDouble::from(k as f64);
+            sum += term;
+            if term.hi.abs() <= EPSILON * sum.hi.abs() {
+                break;
+            }
+        }
+        sum
+    }
+
+    /// Sine and cosine by Taylor series, for `|self| <= pi / 4`.
+    fn sin_cos_taylor(self) -> (Self, Self) {
+        let square = self * self;
+        let mut sin_term = self;
+        let mut sin = self;
+        let mut cos_term = DoubleDouble::one();
+        let mut cos = DoubleDouble::one();
+        for k in 1..30 {
+            let k = k as f64;
+            sin_term = -sin_term * square / DoubleDouble::from(2.0 * k * (2.0 * k + 1.0));
+            cos_term = -cos_term * square / DoubleDouble::from((2.0 * k - 1.0) * 2.0 * k);
+            sin += sin_term;
+            cos += cos_term;
+            if cos_term.hi.abs() <= EPSILON && sin_term.hi.abs() <= EPSILON * sin.hi.abs() {
+                break;
+            }
+        }
+        (sin, cos)
+    }
+
+    fn from_i128(value: i128) -> Self {
+        let hi = value as f64;
+        DoubleDouble::new(hi, (value - hi as i128) as f64)
+    }
+
+    fn to_i128(self) -> Option<i128> {
+        if !self.hi.is_finite() || self.hi.abs() >= 2f64.powi(126) {
+            return None;
+        }
+        let truncated = self.trunc();
+        Some(truncated.hi as i128 + truncated.lo as i128)
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        DoubleDouble { hi: value, lo: 0.0 }
+    }
+}
+
+impl From<f32> for DoubleDouble {
+    fn from(value: f32) -> Self {
+        DoubleDouble::from(f64::from(value))
+    }
+}
+
+impl From<i32> for DoubleDouble {
+    fn from(value: i32) -> Self {
+        DoubleDouble::from(f64::from(value))
+    }
+}
+
+impl From<i64> for DoubleDouble {
+    fn from(value: i64) -> Self {
+        DoubleDouble::from_i128(i128::from(value))
+    }
+}
+
+impl From<u64> for DoubleDouble {
+    fn from(value: u64) -> Self {
+        DoubleDouble::from_i128(i128::from(value))
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (s1, s2) = two_sum(self.hi, other.hi);
+        if !s1.is_finite() {
+            return DoubleDouble::from(s1);
+        }
+        let (t1, t2) = two_sum(self.lo, other.lo);
+        let (s1, s2) = quick_two_sum(s1, s2 + t1);
+        DoubleDouble::from_quick_sum(s1, s2 + t2)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let (p1, p2) = two_prod(self.hi, other.hi);
+        DoubleDouble::from_quick_sum(p1, p2 + (self.hi * other.lo + self.lo * other.hi))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let q1 = self.hi / other.hi;
+        if !q1.is_finite() || !other.hi.is_finite() {
+            return DoubleDouble::from(q1);
+        }
+        let r = self - other.mul_f64(q1);
+        let q2 = r.hi / other.hi;
+        let r = r - other.mul_f64(q2);
+        let q3 = r.hi / other.hi;
+        DoubleDouble::from_quick_sum(q1, q2) + DoubleDouble::from(q3)
+    }
+}
+
+impl Rem for DoubleDouble {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self {
+        self - (self / other).trunc() * other
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        DoubleDouble {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+macro_rules! assign_op {
+    ($assign:ident, $assign_fn:ident, $op_fn:ident) => {
+        impl $assign for DoubleDouble {
+            fn $assign_fn(&mut self, other: Self) {
+                *self = (*self).$op_fn(other);
+            }
+        }
+    };
+}
+
+assign_op!(AddAssign, add_assign, add);
+assign_op!(SubAssign, sub_assign, sub);
+assign_op!(MulAssign, mul_assign, mul);
+assign_op!(DivAssign, div_assign, div);
+assign_op!(RemAssign, rem_assign, rem);
+
+impl PartialOrd for DoubleDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.hi.partial_cmp(&other.hi)? {
+            Ordering::Equal => self.lo.partial_cmp(&other.lo),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+impl Zero for DoubleDouble {
+    fn zero() -> Self {
+        DoubleDouble::from(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.hi == 0.0
+    }
+}
+
+impl One for DoubleDouble {
+    fn one() -> Self {
+        DoubleDouble::from(1.0)
+    }
+}
+
+/// Error returned when parsing a [`DoubleDouble`] fails.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseDoubleDoubleError;
+
+impl fmt::Display for ParseDoubleDoubleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid double-double literal")
+    }
+}
+
+impl std::error::Error for ParseDoubleDoubleError {}
+
+impl Num for DoubleDouble {
+    type FromStrRadixErr = ParseDoubleDoubleError;
+
+    /// Only decimal literals are supported.
+    fn from_str_radix(source: &str, radix: u32) -> Result<Self, ParseDoubleDoubleError> {
+        if radix != 10 {
+            return Err(ParseDoubleDoubleError);
+        }
+        source.parse()
+    }
+}
+
+/// Parses decimal literals such as `-0.743643887037158704752191506114774e-3` to full precision.
+impl FromStr for DoubleDouble {
+    type Err = ParseDoubleDoubleError;
+
+    fn from_str(source: &str) -> Result<Self, ParseDoubleDoubleError> {
+        let source = source.trim();
+        let (negative, unsigned) = match source.as_bytes().first() {
+            Some(b'-') => (true, &source[1..]),
+            Some(b'+') => (false, &source[1..]),
+            _ => (false, source),
+        };
+        let special = match unsigned.to_ascii_lowercase().as_str() {
+            "inf" | "infinity" => Some(f64::INFINITY),
+            "nan" => Some(f64::NAN),
+            _ => None,
+        };
+        if let Some(special) = special {
+            return Ok(DoubleDouble::from(if negative {
+                -special
+            } else {
+                special
+            }));
+        }
+
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(at) => (
+                &unsigned[..at],
+                unsigned[at + 1..]
+                    .parse::<i32>()
+                    .map_err(|_| ParseDoubleDoubleError)?,
+            ),
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = match mantissa.find('.') {
+            Some(at) => (&mantissa[..at], &mantissa[at + 1..]),
+            None => (mantissa, ""),
+        };
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(ParseDoubleDoubleError);
+        }
+
+        let ten = DoubleDouble::from(10.0);
+        let mut value = DoubleDouble::zero();
+        for c in integer.chars().chain(fraction.chars()) {
+            let digit = c.to_digit(10).ok_or(ParseDoubleDoubleError)?;
+            value = value * ten + DoubleDouble::from(f64::from(digit));
+        }
+        let exponent = exponent - fraction.len() as i32;
+        if exponent >= 0 {
+            value *= ten.powi(exponent);
+        } else {
+            value /= ten.powi(-exponent);
+        }
+        Ok(if negative { -value } else { value })
+    }
+}
+
+/// Scientific notation with 32 significant digits, or `precision + 1` if a precision is given.
+impl fmt::Display for DoubleDouble {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.hi.is_nan() || self.hi.is_infinite() || self.hi == 0.0 {
+            return fmt::Display::fmt(&self.hi, f);
+        }
+        let ten = DoubleDouble::from(10.0);
+        let mut x = self.abs();
+        let mut exponent = x.hi.log10().floor() as i32;
+        x = if exponent >= 0 {
+            x / ten.powi(exponent)
+        } else {
+            x * ten.powi(-exponent)
+        };
+        while x >= ten {
+            x /= ten;
+            exponent += 1;
+        }
+        while x < DoubleDouble::one() {
+            x *= ten;
+            exponent -= 1;
+        }
+
+        let count = f.precision().unwrap_or(31) + 1;
+        let mut digits = Vec::with_capacity(count + 1);
+        for _ in 0..=count {
+            let digit = x.floor().hi.clamp(0.0, 9.0);
+            digits.push(digit as u8);
+            x = (x - DoubleDouble::from(digit)) * ten;
+        }
+        // round half up on the extra digit
+        if digits.pop() >= Some(5) {
+            let mut i = digits.len();
+            loop {
+                if i == 0 {
+                    digits.insert(0, 1);
+                    digits.pop();
+                    exponent += 1;
+                    break;
+                }
+                i -= 1;
+                if digits[i] == 9 {
+                    digits[i] = 0;
+                } else {
+                    digits[i] += 1;
+                    break;
+                }
+            }
+        }
+
+        let mut text = String::with_capacity(count + 8);
+        if self.hi < 0.0 {
+            text.push('-');
+        }
+        text.push((b'0' + digits[0]) as char);
+        if digits.len() > 1 {
+            text.push('.');
+            text.extend(digits[1..].iter().map(|&d| (b'0' + d) as char));
+        }
+        text.push('e');
+        text.push_str(&exponent.to_string());
+        f.write_str(&text)
+    }
+}
+
+impl ToPrimitive for DoubleDouble {
+    fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|value| value.to_i64())
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_i128().and_then(|value| value.to_u64())
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        DoubleDouble::to_i128(*self)
+    }
+
+    fn to_f32(&self) -> Option<f32> {
+        Some(self.hi as f32)
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.hi)
+    }
+}
+
+impl num_traits::NumCast for DoubleDouble {
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        let hi = n.to_f64()?;
+        // large integers do not fit into a single f64
+        if hi.fract() == 0.0 && hi.abs() >= 2f64.powi(53) {
+            if let Some(value) = n.to_i128() {
+                return Some(DoubleDouble::from_i128(value));
+            }
+        }
+        Some(hi.into())
+    }
+}
+
+impl Float for DoubleDouble {
+    fn nan() -> Self {
+        DoubleDouble::from(f64::NAN)
+    }
+
+    fn infinity() -> Self {
+        DoubleDouble::from(f64::INFINITY)
+    }
+
+    fn neg_infinity() -> Self {
+        DoubleDouble::from(f64::NEG_INFINITY)
+    }
+
+    fn neg_zero() -> Self {
+        DoubleDouble::from(-0.0)
+    }
+
+    fn min_value() -> Self {
+        -DoubleDouble::max_value()
+    }
+
+    fn min_positive_value() -> Self {
+        DoubleDouble::from(f64::MIN_POSITIVE)
+    }
+
+    fn epsilon() -> Self {
+        DoubleDouble::from(EPSILON)
+    }
+
+    fn max_value() -> Self {
+        DoubleDouble {
+            hi: f64::MAX,
+            lo: 9.979_201_547_673_598e291,
+        }
+    }
+
+    fn is_nan(self) -> bool {
+        self.hi.is_nan()
+    }
+
+    fn is_infinite(self) -> bool {
+        self.hi.is_infinite()
+    }
+
+    fn is_finite(self) -> bool {
+        self.hi.is_finite()
+    }
+
+    fn is_normal(self) -> bool {
+        self.hi.is_normal()
+    }
+
+    fn classify(self) -> FpCategory {
+        self.hi.classify()
+    }
+
+    fn floor(self) -> Self {
+        let hi = self.hi.floor();
+        if hi == self.hi {
+            DoubleDouble::from_quick_sum(hi, self.lo.floor())
+        } else {
+            DoubleDouble::from(hi)
+        }
+    }
+
+    fn ceil(self) -> Self {
+        let hi = self.hi.ceil();
+        if hi == self.hi {
+            DoubleDouble::from_quick_sum(hi, self.lo.ceil())
+        } else {
+            DoubleDouble::from(hi)
+        }
+    }
+
+    /// Rounds half-way cases away from zero, like `f64::round`.
+    fn round(self) -> Self {
+        let hi = self.hi.round();
+        if hi == self.hi {
+            let lo = if (self.lo - self.lo.trunc()).abs() != 0.5 {
+                self.lo.round()
+            } else if hi > 0.0 {
+                self.lo.ceil()
+            } else {
+                self.lo.floor()
+            };
+            DoubleDouble::from_quick_sum(hi, lo)
+        } else if (hi - self.hi).abs() == 0.5 && self.lo != 0.0 && (hi - self.hi) * self.lo < 0.0 {
+            // a tie of the leading component broken by the trailing one
+            DoubleDouble::from(hi - (hi - self.hi).signum())
+        } else {
+            DoubleDouble::from(hi)
+        }
+    }
+
+    fn trunc(self) -> Self {
+        if self.hi < 0.0 {
+            self.ceil()
+        } else {
+            self.floor()
+        }
+    }
+
+    fn fract(self) -> Self {
+        self - self.trunc()
+    }
+
+    fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    fn signum(self) -> Self {
+        DoubleDouble::from(self.hi.signum())
+    }
+
+    fn is_sign_positive(self) -> bool {
+        self.hi.is_sign_positive()
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.hi.is_sign_negative()
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn recip(self) -> Self {
+        DoubleDouble::one() / self
+    }
+
+    fn powi(self, n: i32) -> Self {
+        let mut base = self;
+        let mut exponent = n.unsigned_abs();
+        let mut result = DoubleDouble::one();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        if n < 0 {
+            result.recip()
+        } else {
+            result
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        if n.fract().is_zero() && n.abs().hi <= f64::from(i32::MAX) {
+            return self.powi(n.hi as i32);
+        }
+        (n * self.ln()).exp()
+    }
+
+    fn sqrt(self) -> Self {
+        if self.hi <= 0.0 || !self.hi.is_finite() {
+            return DoubleDouble::from(self.hi.sqrt());
+        }
+        // one Newton step on the f64 reciprocal square root
+        let x = 1.0 / self.hi.sqrt();
+        let ax = self.hi * x;
+        let ax_dd = DoubleDouble::from(ax);
+        let (hi, lo) = two_sum(ax, (self - ax_dd * ax_dd).hi * (x * 0.5));
+        DoubleDouble { hi, lo }
+    }
+
+    fn exp(self) -> Self {
+        if self.hi.is_nan() || self.hi > 709.8 || self.hi < -745.2 {
+            return DoubleDouble::from(self.hi.exp());
+        }
+        // exp(k ln 2 + 512 r) = 2^k (1 + expm1(r))^512
+        let k = (self.hi / LN_2.hi).round();
+        let r = (self - LN_2 * DoubleDouble::from(k)).scale(-9);
+        let mut s = r.exp_m1_taylor();
+        for _ in 0..9 {
+            s = s.scale(1) + s * s;
+        }
+        (s + DoubleDouble::one()).scale(k as i32)
+    }
+
+    fn exp2(self) -> Self {
+        (self * LN_2).exp()
+    }
+
+    fn ln(self) -> Self {
+        if self.hi <= 0.0 || !self.hi.is_finite() {
+            return DoubleDouble::from(self.hi.ln());
+        }
+        // one Newton step on exp(y) = x
+        let y = DoubleDouble::from(self.hi.ln());
+        y + self * (-y).exp() - DoubleDouble::one()
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        self.ln() / LN_2
+    }
+
+    fn log10(self) -> Self {
+        self.ln() / LN_10
+    }
+
+    fn max(self, other: Self) -> Self {
+        if self.is_nan() || self < other {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if self.is_nan() || self > other {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn abs_sub(self, other: Self) -> Self {
+        if self <= other {
+            DoubleDouble::zero()
+        } else {
+            self - other
+        }
+    }
+
+    fn cbrt(self) -> Self {
+        if self.is_zero() || !self.hi.is_finite() {
+            return self;
+        }
+        let y = DoubleDouble::from(self.hi.cbrt());
+        y - (y * y * y - self) / (DoubleDouble::from(3.0) * y * y)
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        let largest = self.hi.abs().max(other.hi.abs());
+        if largest == 0.0 || !largest.is_finite() {
+            return DoubleDouble::from(self.hi.hypot(other.hi));
+        }
+        // scale by a power of two to avoid overflow of the squares
+        let exponent = largest.log2().floor() as i32;
+        let (x, y) = (self.scale(-exponent), other.scale(-exponent));
+        (x * x + y * y).sqrt().scale(exponent)
+    }
+
+    fn sin(self) -> Self {
+        self.sin_cos().0
+    }
+
+    fn cos(self) -> Self {
+        self.sin_cos().1
+    }
+
+    fn tan(self) -> Self {
+        let (sin, cos) = self.sin_cos();
+        sin / cos
+    }
+
+    fn asin(self) -> Self {
+        if self.abs() > DoubleDouble::one() {
+            return DoubleDouble::nan();
+        }
+        self.atan2((DoubleDouble::one() - self * self).sqrt())
+    }
+
+    fn acos(self) -> Self {
+        if self.abs() > DoubleDouble::one() {
+            return DoubleDouble::nan();
+        }
+        (DoubleDouble::one() - self * self).sqrt().atan2(self)
+    }
+
+    fn atan(self) -> Self {
+        self.atan2(DoubleDouble::one())
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        let (y, x) = (self, other);
+        if !x.hi.is_finite() || !y.hi.is_finite() || x.is_zero() || y.is_zero() {
+            return DoubleDouble::from(y.hi.atan2(x.hi));
+        }
+        // one Newton step on the f64 angle
+        let radius = x.hypot(y);
+        let (x, y) = (x / radius, y / radius);
+        let z = DoubleDouble::from(self.hi.atan2(other.hi));
+        let (sin, cos) = z.sin_cos();
+        if x.hi.abs() > y.hi.abs() {
+            z + (y - sin) / cos
+        } else {
+            z - (x - cos) / sin
+        }
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        if !self.hi.is_finite() {
+            return (DoubleDouble::nan(), DoubleDouble::nan());
+        }
+        let r = self - TAU * (self / TAU).round();
+        let quadrant = (r.hi / FRAC_PI_2.hi).round();
+        let (sin, cos) = (r - FRAC_PI_2 * DoubleDouble::from(quadrant)).sin_cos_taylor();
+        match quadrant as i32 {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            -1 => (-cos, sin),
+            _ => (-sin, -cos),
+        }
+    }
+
+    fn exp_m1(self) -> Self {
+        if self.hi.abs() < 0.5 {
+            self.exp_m1_taylor()
+        } else {
+            self.exp() - DoubleDouble::one()
+        }
+    }
+
+    fn ln_1p(self) -> Self {
+        if self.hi <= -1.0 || !self.hi.is_finite() {
+            return DoubleDouble::from(self.hi.ln_1p());
+        }
+        // one Newton step on expm1(y) = x
+        let y = DoubleDouble::from(self.hi.ln_1p());
+        let e = y.exp_m1();
+        y - (e - self) / (e + DoubleDouble::one())
+    }
+
+    fn sinh(self) -> Self {
+        if self.hi.abs() < 0.5 {
+            let e = self.exp_m1();
+            return (e + e / (e + DoubleDouble::one())).scale(-1);
+        }
+        let e = self.exp();
+        (e - e.recip()).scale(-1)
+    }
+
+    fn cosh(self) -> Self {
+        let e = self.exp();
+        (e + e.recip()).scale(-1)
+    }
+
+    fn tanh(self) -> Self {
+        if self.hi.abs() > 40.0 {
+            return self.signum();
+        }
+        self.sinh() / self.cosh()
+    }
+
+    fn asinh(self) -> Self {
+        if self.hi < 0.0 {
+            return -(-self).asinh();
+        }
+        (self + (self * self + DoubleDouble::one()).sqrt()).ln()
+    }
+
+    fn acosh(self) -> Self {
+        if self < DoubleDouble::one() {
+            return DoubleDouble::nan();
+        }
+        (self + (self * self - DoubleDouble::one()).sqrt()).ln()
+    }
+
+    fn atanh(self) -> Self {
+        let one = DoubleDouble::one();
+        ((one + self) / (one - self)).ln().scale(-1)
+    }
+
+    /// Decodes the leading component only.
+    fn integer_decode(self) -> (u64, i16, i8) {
+        Float::integer_decode(self.hi)
+    }
+
+    fn to_degrees(self) -> Self {
+        self * DoubleDouble::from(180.0) / PI
+    }
+
+    fn to_radians(self) -> Self {
+        self * PI / DoubleDouble::from(180.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::{Float, ToPrimitive};
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use super::DoubleDouble;
+    use crate::viewport::Viewport;
+    use crate::{julia, RenderParams};
+
+    fn dd(source: &str) -> DoubleDouble {
+        source.parse().unwrap()
+    }
+
+    fn assert_close(actual: DoubleDouble, expected: DoubleDouble, tolerance: f64) {
+        let error = (actual - expected).abs() / expected.abs().max(DoubleDouble::from(1e-300));
+        assert!(
+            error.hi() <= tolerance,
+            "{} != {} (relative error {:e})",
+            actual,
+            expected,
+            error.hi()
+        );
+    }
+
+    #[test]
+    fn arithmetic_keeps_digits_beyond_f64() {
+        let one = DoubleDouble::from(1.0);
+        let tiny = DoubleDouble::from(1e-20);
+        assert_eq!((one + tiny) - one, tiny);
+        let third = one / DoubleDouble::from(3.0);
+        assert_close(third * DoubleDouble::from(3.0), one, 1e-31);
+        assert!(third.lo() != 0.0);
+        assert_close(
+            DoubleDouble::from(2.0).sqrt(),
+            dd("1.4142135623730950488016887242096981"),
+            1e-31,
+        );
+        assert_close(
+            DoubleDouble::from(10.0) % DoubleDouble::from(3.0),
+            DoubleDouble::from(1.0),
+            1e-31,
+        );
+    }
+
+    #[test]
+    fn two_prod_is_exact() {
+        let pairs = [
+            (1.0 / 3.0, 3.0),
+            (0.1, 0.7),
+            (-1.234_567_890_123_456_7e-150, 9.876_543_210_987_654e120),
+            (1.5e300, -3.3),
+            (std::f64::consts::PI, 1e-300),
+        ];
+        for &(a, b) in &pairs {
+            let (p, error) = super::two_prod(a, b);
+            assert_eq!(p, a * b);
+            assert_eq!(error, a.mul_add(b, -p), "{} * {}", a, b);
+        }
+    }
+
+    #[test]
+    fn transcendental_functions() {
+        assert_close(
+            DoubleDouble::from(1.0).exp(),
+            dd("2.7182818284590452353602874713526625"),
+            1e-30,
+        );
+        assert_close(
+            DoubleDouble::from(10.0).ln(),
+            dd("2.3025850929940456840179914546843642"),
+            1e-30,
+        );
+        let pi = dd("3.1415926535897932384626433832795029");
+        assert_close(
+            (pi / DoubleDouble::from(6.0)).sin(),
+            DoubleDouble::from(0.5),
+            1e-30,
+        );
+        assert_close(
+            (pi / DoubleDouble::from(3.0)).cos(),
+            DoubleDouble::from(0.5),
+            1e-30,
+        );
+        assert_close(
+            DoubleDouble::from(1.0).atan2(DoubleDouble::from(-1.0)),
+            pi * DoubleDouble::from(0.75),
+            1e-30,
+        );
+        let x = dd("0.123456789012345678901234567890");
+        assert_close(x.exp().ln(), x, 1e-30);
+        assert_close(x.powf(DoubleDouble::from(2.5)), x * x * x.sqrt(), 1e-29);
+    }
+
+    #[test]
+    fn rounding_and_conversions() {
+        let half = DoubleDouble::from(0.5);
+        assert_eq!(
+            (DoubleDouble::from(2.0) + half).round(),
+            DoubleDouble::from(3.0)
+        );
+        assert_eq!(
+            (DoubleDouble::from(2.5) - DoubleDouble::from(1e-20)).round(),
+            DoubleDouble::from(2.0)
+        );
+        assert_eq!(DoubleDouble::from(-2.5).floor(), DoubleDouble::from(-3.0));
+
+        let large = i64::MAX - 1;
+        let value = <DoubleDouble as num_traits::NumCast>::from(large).unwrap();
+        assert_eq!(value.to_i64(), Some(large));
+        assert_eq!(value.to_f64(), Some(large as f64));
+    }
+
+    #[test]
+    fn parses_and_displays_all_digits() {
+        let text = "-7.4364388703715870475219150611477e-1";
+        assert_eq!(dd(text).to_string(), text);
+        assert_eq!(format!("{:.3}", dd("123456")), "1.235e5");
+        assert_eq!(format!("{:.2}", dd("9.999")), "1.00e1");
+        assert!("1.2.3".parse::<DoubleDouble>().is_err());
+    }
+
+    #[test]
+    fn renders_below_f64_resolution() {
+        // under z^2 points just outside the unit circle escape, points just inside converge to 0
+        let x = DoubleDouble::from(1.0);
+        let half = DoubleDouble::from(2e-20);
+        let zero = DoubleDouble::from(0.0);
+        let results = julia::calc_screen_space(
+            Viewport::from_bounds((x - half, x + half), (zero - half, zero + half), (4, 1)),
+            (zero, zero),
+            RenderParams::default(),
+        )
+        .collect::<Vec<_>>();
+        assert!(results[0].is_stable());
+        assert!(!results[3].is_stable());
+    }
+}
@@ -0,0 +1,723 @@
+use std::cmp::Ordering;
+use std::f64::consts;
+use std::fmt;
+use std::num::FpCategory;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+use num_traits::{Float, Num, One, ToPrimitive, Zero};
+
+/// `f64` mantissa with a separate `i64` binary exponent, for values far outside the `f64`
+/// exponent range such as pixel offsets of very deep zooms.
+///
+/// The precision is that of `f64`. Transcendental functions are evaluated in `f64` after
+/// removing the exponent where possible; [`Float::integer_decode`] saturates the exponent to
+/// the `i16` range.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ExtendedFloat {
+    /// `0.5 <= |mantissa| < 1`, or zero or non-finite with a zero exponent.
+    mantissa: f64,
+    exponent: i64,
+}
+
+/// Exponents beyond which mantissas scaled to the same exponent no longer overlap.
+const MAX_SHIFT: i64 = 64;
+
+/// `value * 2^exponent` in `f64`, saturating to zero or infinity.
+fn scale(value: f64, exponent: i64) -> f64 {
+    let exponent = exponent.clamp(-2200, 2200) as i32;
+    let half = exponent / 2;
+    value * 2f64.powi(half) * 2f64.powi(exponent - half)
+}
+
+impl ExtendedFloat {
+    /// `mantissa * 2^exponent`.
+    pub fn new(mantissa: f64, exponent: i64) -> Self {
+        if mantissa == 0.0 || !mantissa.is_finite() {
+            return ExtendedFloat {
+                mantissa,
+                exponent: 0,
+            };
+        }
+        let bits = mantissa.to_bits();
+        let biased = ((bits >> 52) & 0x7ff) as i64;
+        if biased == 0 {
+            // subnormal mantissa
+            return ExtendedFloat::new(mantissa * 2f64.powi(64), exponent.saturating_sub(64));
+        }
+        ExtendedFloat {
+            mantissa: f64::from_bits((bits & !(0x7ff << 52)) | (1022 << 52)),
+            exponent: exponent.saturating_add(biased - 1022),
+        }
+    }
+
+    /// Normalized mantissa, `0.5 <= |mantissa| < 1` for finite non-zero values.
+    pub fn mantissa(self) -> f64 {
+        self.mantissa
+    }
+
+    /// Binary exponent.
+    pub fn exponent(self) -> i64 {
+        self.exponent
+    }
+
+    fn is_special(self) -> bool {
+        self.mantissa == 0.0 || !self.mantissa.is_finite()
+    }
+
+    /// Applies an integer rounding function. Values below `2^-60` in magnitude round like
+    /// `2^-60` of the same sign, values of `2^53` and above are integers already.
+    fn round_with(self, f: fn(f64) -> f64) -> Self {
+        if self.is_special() || self.exponent > 53 {
+            self
+        } else if self.exponent < -60 {
+            ExtendedFloat::from(f(self.mantissa.signum() * 2f64.powi(-60)))
+        } else {
+            ExtendedFloat::from(f(scale(self.mantissa, self.exponent)))
+        }
+    }
+
+    /// Evaluates an odd function `f` with `f(x) = x + O(x^3)` near zero.
+    fn odd(self, f: fn(f64) -> f64) -> Self {
+        if self.exponent < -30 {
+            self
+        } else {
+            ExtendedFloat::from(f(self.to_f64_lossy()))
+        }
+    }
+
+    fn to_f64_lossy(self) -> f64 {
+        scale(self.mantissa, self.exponent)
+    }
+
+    /// `2^power`, keeping the integer part of `power` in the exponent.
+    fn from_exp2(power: f64) -> Self {
+        if power.is_nan() {
+            return ExtendedFloat::nan();
+        }
+        if power.abs() >= 9.2e18 {
+            return if power > 0.0 {
+                ExtendedFloat::infinity()
+            } else {
+                ExtendedFloat::zero()
+            };
+        }
+        let whole = power.floor();
+        ExtendedFloat::new((power - whole).exp2(), whole as i64)
+    }
+}
+
+impl From<f64> for ExtendedFloat {
+    fn from(value: f64) -> Self {
+        ExtendedFloat::new(value, 0)
+    }
+}
+
+impl From<f32> for ExtendedFloat {
+    fn from(value: f32) -> Self {
+        ExtendedFloat::from(f64::from(value))
+    }
+}
+
+impl From<i32> for ExtendedFloat {
+    fn from(value: i32) -> Self {
+        ExtendedFloat::from(f64::from(value))
+    }
+}
+
+impl Add for ExtendedFloat {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        if self.is_special() || other.is_special() {
+            if self.mantissa == 0.0 {
+                return other;
+            }
+            if other.mantissa == 0.0 {
+                return self;
+            }
+            return ExtendedFloat::from(self.mantissa + other.mantissa);
+        }
+        let difference = self.exponent - other.exponent;
+        if difference > MAX_SHIFT {
+            self
+        } else if difference < -MAX_SHIFT {
+            other
+        } else if difference >= 0 {
+            ExtendedFloat::new(
+                self.mantissa + scale(other.mantissa, -difference),
+                self.exponent,
+            )
+        } else {
+            ExtendedFloat::new(
+                scale(self.mantissa, difference) + other.mantissa,
+                other.exponent,
+            )
+        }
+    }
+}
+
+impl Sub for ExtendedFloat {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for ExtendedFloat {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        ExtendedFloat::new(
+            self.mantissa * other.mantissa,
+            self.exponent.saturating_add(other.exponent),
+        )
+    }
+}
+
+impl Div for ExtendedFloat {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        ExtendedFloat::new(
+            self.mantissa / other.mantissa,
+            self.exponent.saturating_sub(other.exponent),
+        )
+    }
+}
+
+impl Rem for ExtendedFloat {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self {
+        self - (self / other).trunc() * other
+    }
+}
+
+impl Neg for ExtendedFloat {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ExtendedFloat {
+            mantissa: -self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+}
+
+macro_rules! assign_op {
+    ($assign:ident, $assign_fn:ident, $op_fn:ident) => {
+        impl $assign for ExtendedFloat {
+            fn $assign_fn(&mut self, other: Self) {
+                *self = (*self).$op_fn(other);
+            }
+        }
+    };
+}
+
+assign_op!(AddAssign, add_assign, add);
+assign_op!(SubAssign, sub_assign, sub);
+assign_op!(MulAssign, mul_assign, mul);
+assign_op!(DivAssign, div_assign, div);
+assign_op!(RemAssign, rem_assign, rem);
+
+impl PartialOrd for ExtendedFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let signs_differ = (self.mantissa < 0.0) != (other.mantissa < 0.0);
+        if self.is_special() || other.is_special() || signs_differ {
+            return self.mantissa.partial_cmp(&other.mantissa);
+        }
+        let exponents = if self.mantissa < 0.0 {
+            other.exponent.cmp(&self.exponent)
+        } else {
+            self.exponent.cmp(&other.exponent)
+        };
+        match exponents {
+            Ordering::Equal => self.mantissa.partial_cmp(&other.mantissa),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+impl Zero for ExtendedFloat {
+    fn zero() -> Self {
+        ExtendedFloat::from(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.mantissa == 0.0
+    }
+}
+
+impl One for ExtendedFloat {
+    fn one() -> Self {
+        ExtendedFloat::from(1.0)
+    }
+}
+
+/// Error returned when parsing an [`ExtendedFloat`] fails.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseExtendedFloatError;
+
+impl fmt::Display for ParseExtendedFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid extended float literal")
+    }
+}
+
+impl std::error::Error for ParseExtendedFloatError {}
+
+impl Num for ExtendedFloat {
+    type FromStrRadixErr = ParseExtendedFloatError;
+
+    /// Only decimal literals are supported.
+    fn from_str_radix(source: &str, radix: u32) -> Result<Self, ParseExtendedFloatError> {
+        if radix != 10 {
+            return Err(ParseExtendedFloatError);
+        }
+        source.parse()
+    }
+}
+
+/// Parses decimal literals with exponents of any size, such as `1.5e-1000`.
+impl FromStr for ExtendedFloat {
+    type Err = ParseExtendedFloatError;
+
+    fn from_str(source: &str) -> Result<Self, ParseExtendedFloatError> {
+        let source = source.trim();
+        let (mantissa, exponent) = match source.find(['e', 'E']) {
+            Some(at) => (
+                &source[..at],
+                source[at + 1..]
+                    .parse::<i32>()
+                    .map_err(|_| ParseExtendedFloatError)?,
+            ),
+            None => (source, 0),
+        };
+        let mantissa = mantissa
+            .parse::<f64>()
+            .map_err(|_| ParseExtendedFloatError)?;
+        Ok(ExtendedFloat::from(mantissa) * ExtendedFloat::from(10.0).powi(exponent))
+    }
+}
+
+/// Values within the `f64` range are formatted like `f64`, others in scientific notation.
+impl fmt::Display for ExtendedFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_special() || (-1000..1000).contains(&self.exponent) {
+            return fmt::Display::fmt(&self.to_f64_lossy(), f);
+        }
+        let log = self.mantissa.abs().log10() + self.exponent as f64 * consts::LOG10_2;
+        let mut exponent = log.floor();
+        let mut mantissa = 10f64.powf(log - exponent);
+        if mantissa >= 10.0 {
+            mantissa /= 10.0;
+            exponent += 1.0;
+        }
+        let mantissa = mantissa.copysign(self.mantissa);
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*}e{}", precision, mantissa, exponent),
+            None => write!(f, "{}e{}", mantissa, exponent),
+        }
+    }
+}
+
+impl ToPrimitive for ExtendedFloat {
+    fn to_i64(&self) -> Option<i64> {
+        self.to_f64().and_then(|value| value.to_i64())
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_f64().and_then(|value| value.to_u64())
+    }
+
+    /// Saturates to zero or infinity outside the `f64` range.
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.to_f64_lossy())
+    }
+}
+
+impl num_traits::NumCast for ExtendedFloat {
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_f64().map(Into::into)
+    }
+}
+
+impl Float for ExtendedFloat {
+    fn nan() -> Self {
+        ExtendedFloat::from(f64::NAN)
+    }
+
+    fn infinity() -> Self {
+        ExtendedFloat::from(f64::INFINITY)
+    }
+
+    fn neg_infinity() -> Self {
+        ExtendedFloat::from(f64::NEG_INFINITY)
+    }
+
+    fn neg_zero() -> Self {
+        ExtendedFloat::from(-0.0)
+    }
+
+    fn min_value() -> Self {
+        -ExtendedFloat::max_value()
+    }
+
+    fn min_positive_value() -> Self {
+        ExtendedFloat {
+            mantissa: 0.5,
+            exponent: i64::MIN,
+        }
+    }
+
+    fn epsilon() -> Self {
+        ExtendedFloat::from(f64::EPSILON)
+    }
+
+    fn max_value() -> Self {
+        ExtendedFloat {
+            mantissa: 1.0 - f64::EPSILON / 2.0,
+            exponent: i64::MAX,
+        }
+    }
+
+    fn is_nan(self) -> bool {
+        self.mantissa.is_nan()
+    }
+
+    fn is_infinite(self) -> bool {
+        self.mantissa.is_infinite()
+    }
+
+    fn is_finite(self) -> bool {
+        self.mantissa.is_finite()
+    }
+
+    fn is_normal(self) -> bool {
+        !self.is_special()
+    }
+
+    fn classify(self) -> FpCategory {
+        if self.is_special() {
+            self.mantissa.classify()
+        } else {
+            FpCategory::Normal
+        }
+    }
+
+    fn floor(self) -> Self {
+        self.round_with(f64::floor)
+    }
+
+    fn ceil(self) -> Self {
+        self.round_with(f64::ceil)
+    }
+
+    fn round(self) -> Self {
+        self.round_with(f64::round)
+    }
+
+    fn trunc(self) -> Self {
+        self.round_with(f64::trunc)
+    }
+
+    fn fract(self) -> Self {
+        self - self.trunc()
+    }
+
+    fn abs(self) -> Self {
+        ExtendedFloat {
+            mantissa: self.mantissa.abs(),
+            exponent: self.exponent,
+        }
+    }
+
+    fn signum(self) -> Self {
+        ExtendedFloat::from(self.mantissa.signum())
+    }
+
+    fn is_sign_positive(self) -> bool {
+        self.mantissa.is_sign_positive()
+    }
+
+    fn is_sign_negative(self) -> bool {
+        self.mantissa.is_sign_negative()
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn recip(self) -> Self {
+        ExtendedFloat::one() / self
+    }
+
+    fn powi(self, n: i32) -> Self {
+        let mut base = self;
+        let mut exponent = n.unsigned_abs();
+        let mut result = ExtendedFloat::one();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        if n < 0 {
+            result.recip()
+        } else {
+            result
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        if n.fract().is_zero() && n.abs() <= ExtendedFloat::from(f64::from(i32::MAX)) {
+            return self.powi(n.to_f64_lossy() as i32);
+        }
+        (n * self.ln()).exp()
+    }
+
+    fn sqrt(self) -> Self {
+        if self.is_special() || self.mantissa < 0.0 {
+            return ExtendedFloat::from(self.mantissa.sqrt());
+        }
+        let odd = self.exponent.rem_euclid(2);
+        ExtendedFloat::new(scale(self.mantissa, odd).sqrt(), (self.exponent - odd) / 2)
+    }
+
+    fn exp(self) -> Self {
+        ExtendedFloat::from_exp2(self.to_f64_lossy() * consts::LOG2_E)
+    }
+
+    fn exp2(self) -> Self {
+        ExtendedFloat::from_exp2(self.to_f64_lossy())
+    }
+
+    fn ln(self) -> Self {
+        self.log2() * ExtendedFloat::from(consts::LN_2)
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        if self.is_special() || self.mantissa < 0.0 {
+            return ExtendedFloat::from(self.mantissa.log2());
+        }
+        ExtendedFloat::from(self.mantissa.log2() + self.exponent as f64)
+    }
+
+    fn log10(self) -> Self {
+        self.log2() * ExtendedFloat::from(consts::LOG10_2)
+    }
+
+    fn max(self, other: Self) -> Self {
+        if self.is_nan() || self < other {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if self.is_nan() || self > other {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn abs_sub(self, other: Self) -> Self {
+        if self <= other {
+            ExtendedFloat::zero()
+        } else {
+            self - other
+        }
+    }
+
+    fn cbrt(self) -> Self {
+        if self.is_special() {
+            return self;
+        }
+        let rest = self.exponent.rem_euclid(3);
+        ExtendedFloat::new(
+            scale(self.mantissa, rest).cbrt(),
+            (self.exponent - rest) / 3,
+        )
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        let shift = self.exponent.max(other.exponent);
+        if self.is_special() || other.is_special() {
+            return ExtendedFloat::from(self.to_f64_lossy().hypot(other.to_f64_lossy()));
+        }
+        let (x, y) = (
+            scale(self.mantissa, self.exponent - shift),
+            scale(other.mantissa, other.exponent - shift),
+        );
+        ExtendedFloat::new(x.hypot(y), shift)
+    }
+
+    fn sin(self) -> Self {
+        self.odd(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        ExtendedFloat::from(self.to_f64_lossy().cos())
+    }
+
+    fn tan(self) -> Self {
+        self.odd(f64::tan)
+    }
+
+    fn asin(self) -> Self {
+        self.odd(f64::asin)
+    }
+
+    fn acos(self) -> Self {
+        ExtendedFloat::from(self.to_f64_lossy().acos())
+    }
+
+    fn atan(self) -> Self {
+        self.odd(f64::atan)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        // only the ratio matters, remove the common exponent
+        let shift = self.exponent.max(other.exponent);
+        let y = scale(self.mantissa, self.exponent - shift);
+        let x = scale(other.mantissa, other.exponent - shift);
+        ExtendedFloat::from(y.atan2(x))
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), self.cos())
+    }
+
+    fn exp_m1(self) -> Self {
+        self.odd(f64::exp_m1)
+    }
+
+    fn ln_1p(self) -> Self {
+        self.odd(f64::ln_1p)
+    }
+
+    fn sinh(self) -> Self {
+        self.odd(f64::sinh)
+    }
+
+    fn cosh(self) -> Self {
+        let e = self.exp();
+        (e + e.recip()) * ExtendedFloat::from(0.5)
+    }
+
+    fn tanh(self) -> Self {
+        self.odd(f64::tanh)
+    }
+
+    fn asinh(self) -> Self {
+        if self.is_special() || self.exponent < 1000 {
+            return self.odd(f64::asinh);
+        }
+        // asinh(x) = ln(2x) for large x
+        (self.abs() * ExtendedFloat::from(2.0)).ln() * self.signum()
+    }
+
+    fn acosh(self) -> Self {
+        if self.is_special() || self.exponent < 1000 {
+            return ExtendedFloat::from(self.to_f64_lossy().acosh());
+        }
+        (self * ExtendedFloat::from(2.0)).ln()
+    }
+
+    fn atanh(self) -> Self {
+        self.odd(f64::atanh)
+    }
+
+    /// The exponent saturates to the `i16` range.
+    fn integer_decode(self) -> (u64, i16, i8) {
+        let (mantissa, exponent, sign) = Float::integer_decode(self.mantissa);
+        if self.is_special() {
+            return (mantissa, exponent, sign);
+        }
+        let exponent =
+            (i64::from(exponent) + self.exponent).clamp(i64::from(i16::MIN), i64::from(i16::MAX));
+        (mantissa, exponent as i16, sign)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+    use num_traits::{Float, ToPrimitive};
+
+    use super::ExtendedFloat;
+
+    fn ef(source: &str) -> ExtendedFloat {
+        source.parse().unwrap()
+    }
+
+    fn assert_close(actual: ExtendedFloat, expected: ExtendedFloat) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(
+            error <= ExtendedFloat::from(1e-13),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn normalizes_mantissa() {
+        let x = ExtendedFloat::from(12.0);
+        assert_eq!((x.mantissa(), x.exponent()), (0.75, 4));
+        let x = ExtendedFloat::from(-f64::MIN_POSITIVE / 4.0);
+        assert_eq!((x.mantissa(), x.exponent()), (-0.5, -1023));
+        assert_eq!(ExtendedFloat::from(0.0), ExtendedFloat::new(0.0, 12));
+    }
+
+    #[test]
+    fn arithmetic_beyond_f64_range() {
+        let tiny = ef("1e-400");
+        let huge = ef("3e500");
+        assert_eq!(tiny.to_f64(), Some(0.0));
+        assert_close(tiny * huge, ef("3e100"));
+        assert_close(huge / tiny, ef("3e900"));
+        assert_close(tiny + tiny, ef("2e-400"));
+        assert_eq!(ExtendedFloat::from(1.0) + tiny, ExtendedFloat::from(1.0));
+        assert_close((tiny * tiny).sqrt(), tiny);
+        assert!(tiny > ExtendedFloat::from(0.0));
+        assert!(-tiny > -huge);
+        assert!(tiny < ExtendedFloat::from(1e-300));
+    }
+
+    #[test]
+    fn functions_scale_exponents() {
+        let tiny = ef("1e-1000");
+        assert_close(tiny.log10(), ExtendedFloat::from(-1000.0));
+        assert_close(tiny.ln().exp(), tiny);
+        assert_close(
+            tiny.hypot(tiny * ExtendedFloat::from(2.0)),
+            tiny * ef("5").sqrt(),
+        );
+        assert_close(tiny.atan2(-tiny), ef("2.356194490192345"));
+        assert_eq!(tiny.sin(), tiny);
+        assert_eq!(tiny.floor(), ExtendedFloat::from(0.0));
+        assert_eq!((-tiny).floor(), ExtendedFloat::from(-1.0));
+        assert_eq!(ef("2.5").round(), ExtendedFloat::from(3.0));
+    }
+
+    #[test]
+    fn complex_iteration_keeps_tiny_values() {
+        let delta = Complex::new(ef("1e-500"), ef("-2e-500"));
+        let square = delta * delta;
+        assert_close(square.re, ef("-3e-1000"));
+        assert_close(delta.norm(), ef("5e-1000").sqrt());
+        assert_eq!(format!("{:.3}", ef("1.5e-500")), "1.500e-500");
+    }
+}
@@ -0,0 +1,511 @@
+use num_complex::Complex;
+use num_traits::Float;
+
+use crate::RenderParams;
+
+/// An iterated map rendered by [`calc_screen_space`](crate::calc_screen_space).
+///
+/// `point` is the location of the rendered pixel on the complex plane.
+pub trait Formula<F>
+where
+    F: Float,
+{
+    /// Starting value of the orbit of `point`.
+    fn initial(&self, point: Complex<F>) -> Complex<F>;
+
+    /// Next value of the orbit of `point`.
+    fn step(&self, z: Complex<F>, point: Complex<F>) -> Complex<F>;
+
+    /// Whether the orbit is still considered bounded at `z`.
+    fn is_bounded(&self, z: &Complex<F>, params: &RenderParams<F>) -> bool {
+        params.is_bounded(z)
+    }
+
+    /// Degree of the map, used to normalize smooth iteration counts.
+    fn degree(&self) -> F {
+        F::from(2).unwrap()
+    }
+
+    /// Analytic test for points whose orbits are known to stay bounded, so that iterating
+    /// them can be skipped. Must not report false positives.
+    fn is_known_interior(&self, _point: Complex<F>) -> bool {
+        false
+    }
+
+    /// Constant `c` of quadratic maps `z -> s(z) + c` with `|s(z)| = |z|^2`, such as `z^2 + c`
+    /// and its folded variants, for the orbit of `point`; `None` for other maps.
+    fn addend(&self, _point: Complex<F>) -> Option<Complex<F>> {
+        None
+    }
+}
+
+/// A [`Formula`] whose orbit derivative with respect to the pixel can be tracked, as needed
+/// for distance estimation.
+pub trait Differentiable<F>: Formula<F>
+where
+    F: Float,
+{
+    /// Derivative of the initial value with respect to the pixel.
+    fn initial_derivative(&self) -> Complex<F>;
+
+    /// Derivative of `step(z, point)` given `dz`, the derivative of `z`.
+    fn step_derivative(&self, z: Complex<F>, dz: Complex<F>) -> Complex<F>;
+}
+
+/// `z -> z^2 + c`, starting at `z = 0` with `c` being the pixel.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Mandelbrot;
+
+impl<F> Formula<F> for Mandelbrot
+where
+    F: Float,
+{
+    fn initial(&self, _point: Complex<F>) -> Complex<F> {
+        Complex::new(F::zero(), F::zero())
+    }
+
+    fn step(&self, z: Complex<F>, point: Complex<F>) -> Complex<F> {
+        z.powu(2) + point
+    }
+
+    fn addend(&self, point: Complex<F>) -> Option<Complex<F>> {
+        Some(point)
+    }
+
+    /// Main cardioid and period-2 bulb membership.
+    fn is_known_interior(&self, point: Complex<F>) -> bool {
+        let quarter = F::from(0.25).unwrap();
+        let x = point.re - quarter;
+        let y2 = point.im * point.im;
+        let q = x * x + y2;
+        let in_cardioid = q * (q + x) <= quarter * y2;
+
+        let x = point.re + F::one();
+        let in_bulb = x * x + y2 <= quarter * quarter;
+
+        in_cardioid || in_bulb
+    }
+}
+
+impl<F> Differentiable<F> for Mandelbrot
+where
+    F: Float,
+{
+    fn initial_derivative(&self) -> Complex<F> {
+        Complex::new(F::zero(), F::zero())
+    }
+
+    fn step_derivative(&self, z: Complex<F>, dz: Complex<F>) -> Complex<F> {
+        z * dz * F::from(2).unwrap() + F::one()
+    }
+}
+
+/// `z -> z^2 + c` for a fixed `c`, starting at the pixel.
+#[derive(Copy, Clone, Debug)]
+pub struct Julia<F> {
+    pub c: Complex<F>,
+}
+
+impl<F> Formula<F> for Julia<F>
+where
+    F: Float,
+{
+    fn initial(&self, point: Complex<F>) -> Complex<F> {
+        point
+    }
+
+    fn step(&self, z: Complex<F>, _point: Complex<F>) -> Complex<F> {
+        z.powu(2) + self.c
+    }
+
+    fn addend(&self, _point: Complex<F>) -> Option<Complex<F>> {
+        Some(self.c)
+    }
+}
+
+impl<F> Differentiable<F> for Julia<F>
+where
+    F: Float,
+{
+    fn initial_derivative(&self) -> Complex<F> {
+        Complex::new(F::one(), F::zero())
+    }
+
+    fn step_derivative(&self, z: Complex<F>, dz: Complex<F>) -> Complex<F> {
+        z * dz * F::from(2).unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Power<F> {
+    Integer(u32),
+    Real(F),
+}
+
+impl<F> Power<F>
+where
+    F: Float,
+{
+    fn new(exponent: F) -> Self {
+        match exponent.to_u32() {
+            Some(n) if F::from(n).unwrap() == exponent => Power::Integer(n),
+            _ => Power::Real(exponent),
+        }
+    }
+
+    fn apply(&self, z: Complex<F>) -> Complex<F> {
+        match *self {
+            Power::Integer(n) => z.powu(n),
+            Power::Real(exponent) => z.powf(exponent),
+        }
+    }
+
+    fn degree(&self) -> F {
+        match *self {
+            Power::Integer(n) => F::from(n).unwrap(),
+            Power::Real(exponent) => exponent,
+        }
+    }
+
+    fn is_square(&self) -> bool {
+        match *self {
+            Power::Integer(n) => n == 2,
+            Power::Real(_) => false,
+        }
+    }
+
+    /// `d * z^(d - 1)`
+    fn derivative(&self, z: Complex<F>) -> Complex<F> {
+        match *self {
+            Power::Integer(0) => Complex::new(F::zero(), F::zero()),
+            Power::Integer(n) => z.powu(n - 1) * F::from(n).unwrap(),
+            Power::Real(exponent) => z.powf(exponent - F::one()) * exponent,
+        }
+    }
+}
+
+/// `z -> z^d + c` with `c` being the pixel.
+///
+/// Orbits start at `z = 0` for positive exponents and at `z = c` otherwise, as `0^d` is not
+/// finite for `d <= 0`.
+#[derive(Copy, Clone, Debug)]
+pub struct Multibrot<F> {
+    power: Power<F>,
+}
+
+impl<F> Multibrot<F>
+where
+    F: Float,
+{
+    /// Non-negative integer exponents are evaluated with repeated multiplication, others with
+    /// the principal branch of the complex power.
+    pub fn new(exponent: F) -> Self {
+        Multibrot {
+            power: Power::new(exponent),
+        }
+    }
+}
+
+impl<F> Formula<F> for Multibrot<F>
+where
+    F: Float,
+{
+    fn initial(&self, point: Complex<F>) -> Complex<F> {
+        if self.power.degree() > F::zero() {
+            Complex::new(F::zero(), F::zero())
+        } else {
+            point
+        }
+    }
+
+    fn step(&self, z: Complex<F>, point: Complex<F>) -> Complex<F> {
+        self.power.apply(z) + point
+    }
+
+    fn degree(&self) -> F {
+        self.power.degree()
+    }
+
+    fn addend(&self, point: Complex<F>) -> Option<Complex<F>> {
+        if self.power.is_square() {
+            Some(point)
+        } else {
+            None
+        }
+    }
+}
+
+impl<F> Differentiable<F> for Multibrot<F>
+where
+    F: Float,
+{
+    fn initial_derivative(&self) -> Complex<F> {
+        if self.power.degree() > F::zero() {
+            Complex::new(F::zero(), F::zero())
+        } else {
+            Complex::new(F::one(), F::zero())
+        }
+    }
+
+    fn step_derivative(&self, z: Complex<F>, dz: Complex<F>) -> Complex<F> {
+        self.power.derivative(z) * dz + F::one()
+    }
+}
+
+/// `z -> z^d + c` for a fixed `c`, starting at the pixel.
+#[derive(Copy, Clone, Debug)]
+pub struct MultiJulia<F> {
+    pub c: Complex<F>,
+    power: Power<F>,
+}
+
+impl<F> MultiJulia<F>
+where
+    F: Float,
+{
+    /// See [`Multibrot::new`] for how the exponent is evaluated.
+    pub fn new(c: Complex<F>, exponent: F) -> Self {
+        MultiJulia {
+            c,
+            power: Power::new(exponent),
+        }
+    }
+}
+
+impl<F> Formula<F> for MultiJulia<F>
+where
+    F: Float,
+{
+    fn initial(&self, point: Complex<F>) -> Complex<F> {
+        point
+    }
+
+    fn step(&self, z: Complex<F>, _point: Complex<F>) -> Complex<F> {
+        self.power.apply(z) + self.c
+    }
+
+    fn degree(&self) -> F {
+        self.power.degree()
+    }
+
+    fn addend(&self, _point: Complex<F>) -> Option<Complex<F>> {
+        if self.power.is_square() {
+            Some(self.c)
+        } else {
+            None
+        }
+    }
+}
+
+impl<F> Differentiable<F> for MultiJulia<F>
+where
+    F: Float,
+{
+    fn initial_derivative(&self) -> Complex<F> {
+        Complex::new(F::one(), F::zero())
+    }
+
+    fn step_derivative(&self, z: Complex<F>, dz: Complex<F>) -> Complex<F> {
+        self.power.derivative(z) * dz
+    }
+}
+
+macro_rules! escape_time_variant {
+    ($(#[$meta:meta])* $name:ident, $(#[$julia_meta:meta])* $julia:ident, $square:expr) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, Default)]
+        pub struct $name;
+
+        impl<F> Formula<F> for $name
+        where
+            F: Float,
+        {
+            fn initial(&self, _point: Complex<F>) -> Complex<F> {
+                Complex::new(F::zero(), F::zero())
+            }
+
+            fn step(&self, z: Complex<F>, point: Complex<F>) -> Complex<F> {
+                $square(z) + point
+            }
+
+            fn addend(&self, point: Complex<F>) -> Option<Complex<F>> {
+                Some(point)
+            }
+        }
+
+        $(#[$julia_meta])*
+        #[derive(Copy, Clone, Debug)]
+        pub struct $julia<F> {
+            pub c: Complex<F>,
+        }
+
+        impl<F> Formula<F> for $julia<F>
+        where
+            F: Float,
+        {
+            fn initial(&self, point: Complex<F>) -> Complex<F> {
+                point
+            }
+
+            fn step(&self, z: Complex<F>, _point: Complex<F>) -> Complex<F> {
+                $square(z) + self.c
+            }
+
+            fn addend(&self, _point: Complex<F>) -> Option<Complex<F>> {
+                Some(self.c)
+            }
+        }
+    };
+}
+
+fn burning_ship_square<F: Float>(z: Complex<F>) -> Complex<F> {
+    Complex::new(z.re.abs(), z.im.abs()).powu(2)
+}
+
+fn tricorn_square<F: Float>(z: Complex<F>) -> Complex<F> {
+    z.conj().powu(2)
+}
+
+fn celtic_square<F: Float>(z: Complex<F>) -> Complex<F> {
+    let square = z.powu(2);
+    Complex::new(square.re.abs(), square.im)
+}
+
+fn buffalo_square<F: Float>(z: Complex<F>) -> Complex<F> {
+    let square = z.powu(2);
+    Complex::new(square.re.abs(), square.im.abs())
+}
+
+escape_time_variant!(
+    /// Burning Ship, `z -> (|Re z| + i|Im z|)^2 + c`, starting at `z = 0`.
+    BurningShip,
+    /// Julia set of the Burning Ship map for a fixed `c`.
+    BurningShipJulia,
+    burning_ship_square
+);
+
+escape_time_variant!(
+    /// Tricorn (Mandelbar), `z -> conj(z)^2 + c`, starting at `z = 0`.
+    Tricorn,
+    /// Julia set of the Tricorn map for a fixed `c`.
+    TricornJulia,
+    tricorn_square
+);
+
+escape_time_variant!(
+    /// Celtic, `z -> |Re z^2| + i Im z^2 + c`, starting at `z = 0`.
+    Celtic,
+    /// Julia set of the Celtic map for a fixed `c`.
+    CelticJulia,
+    celtic_square
+);
+
+escape_time_variant!(
+    /// Buffalo, `z -> |Re z^2| + i|Im z^2| + c`, starting at `z = 0`.
+    Buffalo,
+    /// Julia set of the Buffalo map for a fixed `c`.
+    BuffaloJulia,
+    buffalo_square
+);
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+
+    use super::{
+        Buffalo, BurningShip, BurningShipJulia, Celtic, Differentiable, Formula, Julia, Mandelbrot,
+        MultiJulia, Multibrot, Tricorn,
+    };
+
+    #[test]
+    fn multibrot_of_degree_two_matches_mandelbrot() {
+        let point = Complex64::new(-0.4, 0.6);
+        let multibrot = Multibrot::new(2.0);
+        let mut z = multibrot.initial(point);
+        let mut w = Formula::<f64>::initial(&Mandelbrot, point);
+        for _ in 0..20 {
+            z = multibrot.step(z, point);
+            w = Mandelbrot.step(w, point);
+            assert_eq!(z, w);
+        }
+    }
+
+    #[test]
+    fn real_exponent_agrees_with_integer_power() {
+        let c = Complex64::new(0.3, -0.2);
+        let integer = MultiJulia::new(c, 3.0);
+        let real = MultiJulia::new(c, 3.0 + 1e-12);
+        let z = Complex64::new(0.5, 0.25);
+        assert!((integer.step(z, z) - real.step(z, z)).norm() < 1e-9);
+        assert_eq!(integer.degree(), 3.0);
+    }
+
+    #[test]
+    fn negative_exponent_starts_at_pixel() {
+        let point = Complex64::new(0.5, 0.5);
+        assert_eq!(Multibrot::new(-2.0).initial(point), point);
+        assert_eq!(Multibrot::new(3.0).initial(point), Complex64::new(0.0, 0.0));
+        let julia = Julia { c: point };
+        assert_eq!(
+            julia.initial(point),
+            MultiJulia::new(point, 2.0).initial(point)
+        );
+    }
+
+    #[test]
+    fn addend_only_for_quadratic_maps() {
+        let point = Complex64::new(0.1, 0.2);
+        let c = Complex64::new(-0.8, 0.156);
+        assert_eq!(Mandelbrot.addend(point), Some(point));
+        assert_eq!(Julia { c }.addend(point), Some(c));
+        assert_eq!(BurningShipJulia { c }.addend(point), Some(c));
+        assert_eq!(Multibrot::new(2.0).addend(point), Some(point));
+        assert_eq!(Multibrot::new(3.0).addend(point), None);
+        assert_eq!(MultiJulia::new(c, 2.5).addend(point), None);
+    }
+
+    #[test]
+    fn variants_fold_the_square() {
+        let point = Complex64::new(0.1, 0.2);
+        let z = Complex64::new(-1.0, -2.0);
+        assert_eq!(
+            BurningShip.step(z, point),
+            Complex64::new(-3.0, 4.0) + point
+        );
+        assert_eq!(Tricorn.step(z, point), Complex64::new(-3.0, -4.0) + point);
+        assert_eq!(Celtic.step(z, point), Complex64::new(3.0, 4.0) + point);
+        assert_eq!(Buffalo.step(z, point), Complex64::new(3.0, 4.0) + point);
+        let z = Complex64::new(1.0, -2.0);
+        assert_eq!(Celtic.step(z, point), Complex64::new(3.0, -4.0) + point);
+        assert_eq!(Buffalo.step(z, point), Complex64::new(3.0, 4.0) + point);
+    }
+
+    #[test]
+    fn julia_variant_uses_fixed_parameter() {
+        let c = Complex64::new(-0.5, 0.3);
+        let julia = BurningShipJulia { c };
+        let z = Complex64::new(-1.0, -2.0);
+        assert_eq!(julia.initial(z), z);
+        assert_eq!(julia.step(z, z), BurningShip.step(z, c));
+    }
+
+    #[test]
+    fn multibrot_derivative_matches_mandelbrot() {
+        let point = Complex64::new(-0.4, 0.6);
+        let multibrot = Multibrot::new(2.0);
+        let (mut z, mut dz) = (Complex64::new(0.0, 0.0), Complex64::new(0.0, 0.0));
+        let (mut w, mut dw) = (z, dz);
+        for _ in 0..10 {
+            dz = multibrot.step_derivative(z, dz);
+            z = multibrot.step(z, point);
+            dw = Mandelbrot.step_derivative(w, dw);
+            w = Mandelbrot.step(w, point);
+            assert_eq!(dz, dw);
+        }
+        let julia = Julia { c: point };
+        let multijulia = MultiJulia::new(point, 2.5);
+        let z = Complex64::new(0.3, 0.1);
+        assert_eq!(julia.step_derivative(z, dz), z * dz * 2.0);
+        assert!((multijulia.step_derivative(z, dz) - z.powf(1.5) * dz * 2.5).norm() < 1e-15);
+    }
+}
@@ -0,0 +1,213 @@
+use num_complex::Complex;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::formula::{Formula, Mandelbrot};
+use crate::viewport::Viewport;
+use crate::{
+    fill_buffer, from_screen_point_to_cartesian, is_stable_by, pixel_count, IterationResult,
+    RenderParams,
+};
+
+/// Attracting cycle an interior orbit of the Mandelbrot set converges to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AttractingCycle<F> {
+    pub period: u64,
+    /// Derivative of the `period`-th iterate along the cycle, `|multiplier| < 1`.
+    pub multiplier: Complex<F>,
+    /// Estimated distance from the pixel to the boundary of its hyperbolic component.
+    pub distance: F,
+}
+
+/// Escape data of a single pixel together with the analysis of its interior.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InteriorEstimate<F> {
+    /// `None` for escaped pixels and when no attracting cycle could be found within the
+    /// iteration limit.
+    pub cycle: Option<AttractingCycle<F>>,
+    pub result: IterationResult<Complex<F>>,
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F>(
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = InteriorEstimate<F>>
+where
+    F: Float,
+{
+    (0..pixel_count(viewport.resolution()))
+        .map(move |index| from_screen_pixel(index, viewport, params))
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F>(
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = InteriorEstimate<F>>
+where
+    F: Float + Send + Sync,
+{
+    (0..pixel_count(viewport.resolution()))
+        .into_par_iter()
+        .map(move |index| from_screen_pixel(index, viewport, params))
+}
+
+/// Like [`calc_screen_space`], but writes `map` of every estimate into `buffer`, see
+/// [`crate::render_into`].
+#[cfg(not(feature = "parallel"))]
+pub fn render_into<F, P, M>(
+    buffer: &mut [P],
+    stride: usize,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+    map: M,
+) where
+    F: Float,
+    M: Fn(InteriorEstimate<F>) -> P,
+{
+    fill_buffer(buffer, stride, viewport.resolution(), |index| {
+        map(from_screen_pixel(index, viewport, params))
+    })
+}
+
+/// Like [`calc_screen_space`], but writes `map` of every estimate into `buffer`, see
+/// [`crate::render_into`].
+#[cfg(feature = "parallel")]
+pub fn render_into<F, P, M>(
+    buffer: &mut [P],
+    stride: usize,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+    map: M,
+) where
+    F: Float + Send + Sync,
+    P: Send,
+    M: Fn(InteriorEstimate<F>) -> P + Send + Sync,
+{
+    fill_buffer(buffer, stride, viewport.resolution(), |index| {
+        map(from_screen_pixel(index, viewport, params))
+    })
+}
+
+fn from_screen_pixel<F>(
+    index: u64,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> InteriorEstimate<F>
+where
+    F: Float,
+{
+    let (x, y) = from_screen_point_to_cartesian(index, viewport);
+    let c = Complex::new(x, y);
+
+    // cycles are only found reliably when converging orbit points are compared with a tolerance
+    let epsilon = params.cycle_epsilon.unwrap_or_else(|| F::epsilon().sqrt());
+    let result = is_stable_by(
+        |z| Mandelbrot.step(z, c),
+        Formula::<F>::initial(&Mandelbrot, c),
+        |z| Mandelbrot.is_bounded(z, &params),
+        params.max_iterations,
+        |a, b| (a - b).norm_sqr() <= epsilon * epsilon,
+    );
+
+    InteriorEstimate {
+        cycle: result
+            .period()
+            .and_then(|period| attracting_cycle(result.value, c, period)),
+        result,
+    }
+}
+
+/// Refines `z` to a periodic point of `z^2 + c` with Newton's method and evaluates the
+/// multiplier and interior distance estimate of its cycle.
+fn attracting_cycle<F>(z: Complex<F>, c: Complex<F>, period: u64) -> Option<AttractingCycle<F>>
+where
+    F: Float,
+{
+    let two = F::from(2).unwrap();
+    let one = Complex::new(F::one(), F::zero());
+    let mut z0 = z;
+    for _ in 0..64 {
+        let (z, dz) = (0..period).fold((z0, one), |(z, dz), _| (z * z + c, z * dz * two));
+        let step = (z - z0) / (dz - one);
+        z0 = z0 - step;
+        if step.norm() <= F::epsilon() * z0.norm().max(F::one()) {
+            break;
+        }
+    }
+
+    let zero = Complex::new(F::zero(), F::zero());
+    let (mut z, mut dz, mut dc, mut dzdz, mut dzdc) = (z0, one, zero, zero, zero);
+    for _ in 0..period {
+        dzdz = (dz * dz + z * dzdz) * two;
+        dzdc = (dz * dc + z * dzdc) * two;
+        dc = z * dc * two + one;
+        dz = z * dz * two;
+        z = z * z + c;
+    }
+
+    let attracting = dz.norm_sqr() < F::one();
+    if !attracting {
+        return None;
+    }
+    Some(AttractingCycle {
+        period,
+        multiplier: dz,
+        distance: (F::one() - dz.norm_sqr()) / (dzdc + dzdz * dc / (one - dz)).norm(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use super::{attracting_cycle, calc_screen_space};
+    use crate::tests::single_pixel;
+    use crate::viewport::Viewport;
+    use crate::RenderParams;
+
+    #[test]
+    fn main_cardioid_center_is_superattracting() {
+        let c = Complex64::new(0.0, 0.0);
+        let cycle = attracting_cycle(c, c, 1).unwrap();
+        assert_eq!(cycle.multiplier, Complex64::new(0.0, 0.0));
+        assert!((cycle.distance - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn finds_period_of_bulbs() {
+        let c = Complex64::new(-1.0, 0.0);
+        assert_eq!(attracting_cycle(c, c, 2).unwrap().period, 2);
+
+        let c = Complex64::new(-0.12, 0.75);
+        let cycle = attracting_cycle(Complex64::new(0.0, 0.0), c, 3).unwrap();
+        assert!(cycle.multiplier.norm() < 1.0);
+        assert!(cycle.distance > 0.0 && cycle.distance < 0.5);
+    }
+
+    #[test]
+    fn repelling_cycle_is_rejected() {
+        let c = Complex64::new(-0.12, 0.75);
+        assert_eq!(attracting_cycle(Complex64::new(0.0, 0.0), c, 1), None);
+    }
+
+    #[test]
+    fn screen_space_reports_cycles_of_interior_pixels() {
+        let results =
+            calc_screen_space(single_pixel(-1.0, 0.0), RenderParams::default()).collect::<Vec<_>>();
+        assert_eq!(results[0].cycle.map(|cycle| cycle.period), Some(2));
+
+        let results = calc_screen_space(
+            Viewport::from_bounds((1.0, 2.0), (1.0, 2.0), (2, 2)),
+            RenderParams::default(),
+        )
+        .collect::<Vec<_>>();
+        assert!(results
+            .iter()
+            .all(|r| r.cycle.is_none() && !r.result.is_stable()));
+    }
+}
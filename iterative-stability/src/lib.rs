@@ -1,247 +1,2520 @@
+use std::fmt;
+
 use num_complex::Complex;
-use num_traits::{Float, NumCast};
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+pub mod accumulator;
+pub mod batch;
+pub mod distance;
+pub mod double_double;
+pub mod extended;
+pub mod formula;
+pub mod interior;
+pub mod newton;
+pub mod perturbation;
+pub mod trap;
+pub mod viewport;
+
+use accumulator::Accumulator;
+use formula::Formula;
+use viewport::Viewport;
+
+/// Reason the iteration in [`is_stable`] stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Termination {
+    /// The stability check failed, the orbit escaped.
+    Escaped,
+    /// The iteration limit was reached while the orbit was still bounded.
+    MaxIterations,
+    /// The orbit entered a cycle of the given period; a fixed point has period 1.
+    Periodic(u64),
+    /// The point was recognized as interior analytically, without iterating.
+    KnownInterior,
+}
+
+/// State of an orbit at the moment the iteration stopped.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct IterationResult<U> {
+    /// Last value of the orbit.
+    pub value: U,
+    /// Number of iterations performed.
+    pub iterations: u64,
+    pub termination: Termination,
+}
+
+impl<U> IterationResult<U> {
+    pub fn is_stable(&self) -> bool {
+        self.termination != Termination::Escaped
+    }
+
+    /// Period of the detected cycle, if the iteration stopped on one.
+    pub fn period(&self) -> Option<u64> {
+        match self.termination {
+            Termination::Periodic(period) => Some(period),
+            _ => None,
+        }
+    }
+
+    fn with_value<V>(&self, value: V) -> IterationResult<V> {
+        IterationResult {
+            value,
+            iterations: self.iterations,
+            termination: self.termination,
+        }
+    }
+}
+
+impl<F> IterationResult<Complex<F>>
+where
+    F: Float,
+{
+    /// Magnitude of the final value, i.e. `|z|` at the moment of escape for escaped orbits.
+    pub fn magnitude(&self) -> F {
+        self.value.norm()
+    }
+
+    /// Normalized (continuous) iteration count of an orbit of `z^degree + c` that escaped past
+    /// `bailout`, or `None` if the orbit is stable, `|degree| <= 1`, where escape is not
+    /// dominated by the power term, or `bailout <= 1`, where its logarithm is not positive.
+    pub fn smooth_iterations(&self, bailout: F, degree: F) -> Option<F> {
+        if self.is_stable() || degree.abs() <= F::one() || bailout <= F::one() {
+            return None;
+        }
+        let n = F::from(self.iterations).unwrap();
+        let log_ratio = self.magnitude().ln() / bailout.ln();
+        Some(n + F::one() - log_ratio.ln() / degree.abs().ln())
+    }
+}
 
 pub fn is_stable<F, G, U>(
     function: F,
     initial: U,
     stability_check: G,
     max_iterations: u64,
-) -> (u64, bool)
+) -> IterationResult<U>
 where
     F: Fn(U) -> U,
     G: Fn(&U) -> bool,
     U: PartialEq + Copy,
+{
+    is_stable_by(
+        function,
+        initial,
+        stability_check,
+        max_iterations,
+        |a, b| a == b,
+    )
+}
+
+/// Like [`is_stable`], but orbit points are compared with `same` when looking for cycles,
+/// e.g. to treat values within rounding distance of each other as equal.
+///
+/// Cycles are detected with Brent's algorithm, so attracting cycles of any period end the
+/// iteration early.
+pub fn is_stable_by<F, G, U, E>(
+    function: F,
+    initial: U,
+    stability_check: G,
+    max_iterations: u64,
+    same: E,
+) -> IterationResult<U>
+where
+    F: Fn(U) -> U,
+    G: Fn(&U) -> bool,
+    E: Fn(&U, &U) -> bool,
+    U: Copy,
 {
     let mut n = initial;
     let mut i: u64 = 0;
-    let mut last = None;
+    let mut saved = initial;
+    let mut power: u64 = 1;
+    let mut lambda: u64 = 0;
+    let result = |value, iterations, termination| IterationResult {
+        value,
+        iterations,
+        termination,
+    };
     loop {
         if !stability_check(&n) {
-            return (i, false);
+            return result(n, i, Termination::Escaped);
         }
         if i == max_iterations {
-            return (i, true);
+            return result(n, i, Termination::MaxIterations);
         }
         n = function(n);
-        if last.is_some() && last.unwrap() == n {
-            return (i, true);
-        }
-        last = Some(n);
         i += 1;
+        lambda += 1;
+        if same(&saved, &n) {
+            return result(n, i, Termination::Periodic(lambda));
+        }
+        if lambda == power {
+            saved = n;
+            power *= 2;
+            lambda = 0;
+        }
     }
 }
 
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = IterationResult<Complex<F>>>
+where
+    F: Float,
+    T: Formula<F>,
+{
+    (0..pixel_count(viewport.resolution()))
+        .map(move |index| from_screen_pixel(&formula, index, viewport, params))
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+{
+    (0..pixel_count(viewport.resolution()))
+        .into_par_iter()
+        .map(move |index| from_screen_pixel(&formula, index, viewport, params))
+}
+
+/// Like [`calc_screen_space`], but only the pixels of `tile` are rendered, row by row. Every
+/// pixel has the same value it has in the full image of `viewport`.
+///
+/// Panics if `tile` does not lie within the image.
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space_tile<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    tile: Tile,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = IterationResult<Complex<F>>>
+where
+    F: Float,
+    T: Formula<F>,
+{
+    let resolution = viewport.resolution();
+    assert!(
+        tile.is_within(resolution),
+        "{:?} exceeds {:?}",
+        tile,
+        resolution
+    );
+    (0..tile.pixel_count()).map(move |index| {
+        from_screen_pixel(
+            &formula,
+            tile.image_index(index, resolution),
+            viewport,
+            params,
+        )
+    })
+}
+
+/// Like [`calc_screen_space`], but only the pixels of `tile` are rendered, row by row. Every
+/// pixel has the same value it has in the full image of `viewport`.
+///
+/// Panics if `tile` does not lie within the image.
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space_tile<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    tile: Tile,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+{
+    let resolution = viewport.resolution();
+    assert!(
+        tile.is_within(resolution),
+        "{:?} exceeds {:?}",
+        tile,
+        resolution
+    );
+    (0..tile.pixel_count()).into_par_iter().map(move |index| {
+        from_screen_pixel(
+            &formula,
+            tile.image_index(index, resolution),
+            viewport,
+            params,
+        )
+    })
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space_smooth<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = Option<F>>
+where
+    F: Float,
+    T: Formula<F>,
+{
+    let degree = formula.degree();
+    calc_screen_space(formula, viewport, params)
+        .map(move |result| result.smooth_iterations(params.bailout, degree))
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space_smooth<F, T>(
+    formula: T,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = Option<F>>
+where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+{
+    let degree = formula.degree();
+    calc_screen_space(formula, viewport, params)
+        .map(move |result| result.smooth_iterations(params.bailout, degree))
+}
+
+/// Like [`calc_screen_space`], but writes `map` of every pixel into `buffer` instead of
+/// allocating, e.g. to reuse a frame buffer. Rows start `stride` elements apart, elements between
+/// the end of a row and the start of the next one are left untouched.
+///
+/// Panics if `stride` is shorter than a row or `buffer` cannot hold the image.
+#[cfg(not(feature = "parallel"))]
+pub fn render_into<F, T, P, M>(
+    formula: T,
+    buffer: &mut [P],
+    stride: usize,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+    map: M,
+) where
+    F: Float,
+    T: Formula<F>,
+    M: Fn(IterationResult<Complex<F>>) -> P,
+{
+    fill_buffer(buffer, stride, viewport.resolution(), |index| {
+        map(from_screen_pixel(&formula, index, viewport, params))
+    })
+}
+
+/// Like [`calc_screen_space_smooth`], but writes `map` of every smooth iteration count into
+/// `buffer`, see [`render_into`].
+#[cfg(not(feature = "parallel"))]
+pub fn render_into_smooth<F, T, P, M>(
+    formula: T,
+    buffer: &mut [P],
+    stride: usize,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+    map: M,
+) where
+    F: Float,
+    T: Formula<F>,
+    M: Fn(Option<F>) -> P,
+{
+    let degree = formula.degree();
+    render_into(formula, buffer, stride, viewport, params, |result| {
+        map(result.smooth_iterations(params.bailout, degree))
+    })
+}
+
+/// Like [`calc_screen_space`], but writes `map` of every pixel into `buffer` instead of
+/// allocating, e.g. to reuse a frame buffer. Rows start `stride` elements apart, elements between
+/// the end of a row and the start of the next one are left untouched.
+///
+/// Panics if `stride` is shorter than a row or `buffer` cannot hold the image.
+#[cfg(feature = "parallel")]
+pub fn render_into<F, T, P, M>(
+    formula: T,
+    buffer: &mut [P],
+    stride: usize,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+    map: M,
+) where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+    P: Send,
+    M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
+{
+    fill_buffer(buffer, stride, viewport.resolution(), |index| {
+        map(from_screen_pixel(&formula, index, viewport, params))
+    })
+}
+
+/// Like [`calc_screen_space_smooth`], but writes `map` of every smooth iteration count into
+/// `buffer`, see [`render_into`].
+#[cfg(feature = "parallel")]
+pub fn render_into_smooth<F, T, P, M>(
+    formula: T,
+    buffer: &mut [P],
+    stride: usize,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+    map: M,
+) where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+    P: Send,
+    M: Fn(Option<F>) -> P + Send + Sync,
+{
+    let degree = formula.degree();
+    render_into(formula, buffer, stride, viewport, params, |result| {
+        map(result.smooth_iterations(params.bailout, degree))
+    })
+}
+
 #[cfg(not(feature = "parallel"))]
 pub mod mandelbrot {
-    use crate::{from_screen_pixel_mandelbrot, SpaceParams};
+    use crate::accumulator::{self, Accumulated, Accumulator};
+    use crate::batch::{self, Kernel, Lanes};
+    use crate::distance::{self, DistanceEstimate};
+    use crate::formula::Mandelbrot;
+    use crate::interior::{self, InteriorEstimate};
+    use crate::perturbation;
+    use crate::trap::{self, Trap, TrapResult};
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams, Tile};
+    use num_complex::Complex;
     use num_traits::Float;
 
     pub fn calc_screen_space<F>(
-        x_bounds: (F, F),
-        y_bounds: (F, F),
-        resolution: (i32, i32),
-    ) -> impl Iterator<Item = (u64, bool)>
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
     where
         F: Float,
     {
-        let sp = SpaceParams::<F>::calc_space_params(x_bounds, y_bounds, resolution);
+        crate::calc_screen_space(Mandelbrot, viewport, params)
+    }
 
-        (0i32..(resolution.0 * resolution.1))
-            .map(move |index| from_screen_pixel_mandelbrot(index, resolution, sp))
+    pub fn calc_screen_space_tile<F>(
+        viewport: Viewport<F>,
+        tile: Tile,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float,
+    {
+        crate::calc_screen_space_tile(Mandelbrot, viewport, tile, params)
+    }
+
+    /// Batched rendering, see [`batch::calc_screen_space`].
+    pub fn calc_screen_space_batched<F>(
+        viewport: Viewport<F>,
+        kernel: Kernel,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Lanes,
+    {
+        batch::calc_screen_space(Mandelbrot, viewport, kernel, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = Option<F>>
+    where
+        F: Float,
+    {
+        crate::calc_screen_space_smooth(Mandelbrot, viewport, params)
+    }
+
+    pub fn calc_screen_space_distance<F>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = DistanceEstimate<F>>
+    where
+        F: Float,
+    {
+        distance::calc_screen_space(Mandelbrot, viewport, params)
+    }
+
+    pub fn calc_screen_space_interior<F>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = InteriorEstimate<F>>
+    where
+        F: Float,
+    {
+        interior::calc_screen_space(viewport, params)
+    }
+
+    /// Perturbation rendering for deep zooms, see [`perturbation::calc_screen_space`].
+    pub fn calc_screen_space_perturbation<F, D>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<D>>>
+    where
+        F: Float,
+        D: Float,
+    {
+        perturbation::calc_screen_space(viewport, params)
+    }
+
+    pub fn calc_screen_space_trap<F, S>(
+        viewport: Viewport<F>,
+        trap: S,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = TrapResult<F>>
+    where
+        F: Float,
+        S: Trap<F>,
+    {
+        trap::calc_screen_space(Mandelbrot, trap, viewport, params)
+    }
+
+    pub fn calc_screen_space_accumulate<F, A>(
+        viewport: Viewport<F>,
+        accumulator: A,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = Accumulated<F, A::Output>>
+    where
+        F: Float,
+        A: Accumulator<F>,
+    {
+        accumulator::calc_screen_space(Mandelbrot, accumulator, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(IterationResult<Complex<F>>) -> P,
+    {
+        crate::render_into(Mandelbrot, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(Option<F>) -> P,
+    {
+        crate::render_into_smooth(Mandelbrot, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders interior estimates into a caller provided buffer, see [`interior::render_into`].
+    pub fn render_into_interior<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(InteriorEstimate<F>) -> P,
+    {
+        interior::render_into(buffer, stride, viewport, params, map)
     }
 }
 
 #[cfg(feature = "parallel")]
 pub mod mandelbrot {
-    use crate::{from_screen_pixel_mandelbrot, SpaceParams};
+    use crate::accumulator::{self, Accumulated, Accumulator};
+    use crate::batch::{self, Kernel, Lanes};
+    use crate::distance::{self, DistanceEstimate};
+    use crate::formula::Mandelbrot;
+    use crate::interior::{self, InteriorEstimate};
+    use crate::perturbation;
+    use crate::trap::{self, Trap, TrapResult};
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams, Tile};
+    use num_complex::Complex;
     use num_traits::Float;
     use rayon::prelude::*;
 
     pub fn calc_screen_space<F>(
-        x_bounds: (F, F),
-        y_bounds: (F, F),
-        resolution: (i32, i32),
-    ) -> impl ParallelIterator<Item = (u64, bool)>
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float + Send + Sync,
+    {
+        crate::calc_screen_space(Mandelbrot, viewport, params)
+    }
+
+    pub fn calc_screen_space_tile<F>(
+        viewport: Viewport<F>,
+        tile: Tile,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float + Send + Sync,
+    {
+        crate::calc_screen_space_tile(Mandelbrot, viewport, tile, params)
+    }
+
+    /// Batched rendering, see [`batch::calc_screen_space`].
+    pub fn calc_screen_space_batched<F>(
+        viewport: Viewport<F>,
+        kernel: Kernel,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Lanes + Send + Sync,
+    {
+        batch::calc_screen_space(Mandelbrot, viewport, kernel, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = Option<F>>
+    where
+        F: Float + Send + Sync,
+    {
+        crate::calc_screen_space_smooth(Mandelbrot, viewport, params)
+    }
+
+    pub fn calc_screen_space_distance<F>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = DistanceEstimate<F>>
     where
         F: Float + Send + Sync,
     {
-        let sp = SpaceParams::<F>::calc_space_params(x_bounds, y_bounds, resolution);
+        distance::calc_screen_space(Mandelbrot, viewport, params)
+    }
+
+    pub fn calc_screen_space_interior<F>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = InteriorEstimate<F>>
+    where
+        F: Float + Send + Sync,
+    {
+        interior::calc_screen_space(viewport, params)
+    }
+
+    /// Perturbation rendering for deep zooms, see [`perturbation::calc_screen_space`].
+    pub fn calc_screen_space_perturbation<F, D>(
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<D>>>
+    where
+        F: Float + Send + Sync,
+        D: Float + Send + Sync,
+    {
+        perturbation::calc_screen_space(viewport, params)
+    }
+
+    pub fn calc_screen_space_trap<F, S>(
+        viewport: Viewport<F>,
+        trap: S,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = TrapResult<F>>
+    where
+        F: Float + Send + Sync,
+        S: Trap<F> + Send + Sync,
+    {
+        trap::calc_screen_space(Mandelbrot, trap, viewport, params)
+    }
+
+    pub fn calc_screen_space_accumulate<F, A>(
+        viewport: Viewport<F>,
+        accumulator: A,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = Accumulated<F, A::Output>>
+    where
+        F: Float + Send + Sync,
+        A: Accumulator<F> + Send + Sync,
+        A::Output: Send,
+    {
+        accumulator::calc_screen_space(Mandelbrot, accumulator, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
+    {
+        crate::render_into(Mandelbrot, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(Option<F>) -> P + Send + Sync,
+    {
+        crate::render_into_smooth(Mandelbrot, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders interior estimates into a caller provided buffer, see [`interior::render_into`].
+    pub fn render_into_interior<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(InteriorEstimate<F>) -> P + Send + Sync,
+    {
+        interior::render_into(buffer, stride, viewport, params, map)
+    }
+}
+
+#[cfg(feature = "parallel")]
+pub mod julia {
+    use crate::accumulator::{self, Accumulated, Accumulator};
+    use crate::batch::{self, Kernel, Lanes};
+    use crate::distance::{self, DistanceEstimate};
+    use crate::formula::Julia;
+    use crate::trap::{self, Trap, TrapResult};
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams, Tile};
+    use num_complex::Complex;
+    use num_traits::Float;
+    use rayon::prelude::*;
+
+    pub fn calc_screen_space<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_tile<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        tile: Tile,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::calc_screen_space_tile(formula, viewport, tile, params)
+    }
+
+    /// Batched rendering, see [`batch::calc_screen_space`].
+    pub fn calc_screen_space_batched<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        kernel: Kernel,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Lanes + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        batch::calc_screen_space(formula, viewport, kernel, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = Option<F>>
+    where
+        F: Float + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::calc_screen_space_smooth(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_distance<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = DistanceEstimate<F>>
+    where
+        F: Float + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        distance::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_trap<F, S>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        trap: S,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = TrapResult<F>>
+    where
+        F: Float + Send + Sync,
+        S: Trap<F> + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        trap::calc_screen_space(formula, trap, viewport, params)
+    }
+
+    pub fn calc_screen_space_accumulate<F, A>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        accumulator: A,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = Accumulated<F, A::Output>>
+    where
+        F: Float + Send + Sync,
+        A: Accumulator<F> + Send + Sync,
+        A::Output: Send,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        accumulator::calc_screen_space(formula, accumulator, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::render_into(formula, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(Option<F>) -> P + Send + Sync,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub mod julia {
+    use crate::accumulator::{self, Accumulated, Accumulator};
+    use crate::batch::{self, Kernel, Lanes};
+    use crate::distance::{self, DistanceEstimate};
+    use crate::formula::Julia;
+    use crate::trap::{self, Trap, TrapResult};
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams, Tile};
+    use num_complex::Complex;
+    use num_traits::Float;
+
+    pub fn calc_screen_space<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_tile<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        tile: Tile,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::calc_screen_space_tile(formula, viewport, tile, params)
+    }
+
+    /// Batched rendering, see [`batch::calc_screen_space`].
+    pub fn calc_screen_space_batched<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        kernel: Kernel,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Lanes,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        batch::calc_screen_space(formula, viewport, kernel, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = Option<F>>
+    where
+        F: Float,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::calc_screen_space_smooth(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_distance<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = DistanceEstimate<F>>
+    where
+        F: Float,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        distance::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_trap<F, S>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        trap: S,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = TrapResult<F>>
+    where
+        F: Float,
+        S: Trap<F>,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        trap::calc_screen_space(formula, trap, viewport, params)
+    }
+
+    pub fn calc_screen_space_accumulate<F, A>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        accumulator: A,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = Accumulated<F, A::Output>>
+    where
+        F: Float,
+        A: Accumulator<F>,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        accumulator::calc_screen_space(formula, accumulator, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(IterationResult<Complex<F>>) -> P,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::render_into(formula, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(Option<F>) -> P,
+    {
+        let formula = Julia {
+            c: Complex::new(c.0, c.1),
+        };
+        crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub mod multibrot {
+    use crate::formula::Multibrot;
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams};
+    use num_complex::Complex;
+    use num_traits::Float;
+
+    pub fn calc_screen_space<F>(
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = Option<F>>
+    where
+        F: Float,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::calc_screen_space_smooth(formula, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(IterationResult<Complex<F>>) -> P,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::render_into(formula, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(Option<F>) -> P,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
+    }
+}
+
+#[cfg(feature = "parallel")]
+pub mod multibrot {
+    use crate::formula::Multibrot;
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams};
+    use num_complex::Complex;
+    use num_traits::Float;
+    use rayon::prelude::*;
+
+    pub fn calc_screen_space<F>(
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float + Send + Sync,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = Option<F>>
+    where
+        F: Float + Send + Sync,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::calc_screen_space_smooth(formula, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::render_into(formula, buffer, stride, viewport, params, map)
+    }
+
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(Option<F>) -> P + Send + Sync,
+    {
+        let formula = Multibrot::new(exponent);
+        crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub mod multijulia {
+    use crate::formula::MultiJulia;
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams};
+    use num_complex::Complex;
+    use num_traits::Float;
+
+    pub fn calc_screen_space<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+    where
+        F: Float,
+    {
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::calc_screen_space(formula, viewport, params)
+    }
+
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
+        c: (F, F),
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl Iterator<Item = Option<F>>
+    where
+        F: Float,
+    {
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::calc_screen_space_smooth(formula, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(IterationResult<Complex<F>>) -> P,
+    {
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::render_into(formula, buffer, stride, viewport, params, map)
+    }
 
-        (0i32..(resolution.0 * resolution.1))
-            .into_par_iter()
-            .map(move |index| from_screen_pixel_mandelbrot(index, resolution, sp))
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float,
+        M: Fn(Option<F>) -> P,
+    {
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
     }
 }
 
 #[cfg(feature = "parallel")]
-pub mod julia {
-    use crate::{from_screen_pixel_julia, SpaceParams};
+pub mod multijulia {
+    use crate::formula::MultiJulia;
+    use crate::viewport::Viewport;
+    use crate::{IterationResult, RenderParams};
+    use num_complex::Complex;
     use num_traits::Float;
     use rayon::prelude::*;
 
     pub fn calc_screen_space<F>(
-        x_bounds: (F, F),
-        y_bounds: (F, F),
-        resolution: (i32, i32),
+        viewport: Viewport<F>,
         c: (F, F),
-    ) -> impl ParallelIterator<Item = (u64, bool)>
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
     where
         F: Float + Send + Sync,
     {
-        let sp = SpaceParams::<F>::calc_space_params(x_bounds, y_bounds, resolution);
-
-        (0i32..(resolution.0 * resolution.1))
-            .into_par_iter()
-            .map(move |index| from_screen_pixel_julia(index, resolution, sp, c))
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::calc_screen_space(formula, viewport, params)
     }
-}
-
-#[cfg(not(feature = "parallel"))]
-pub mod julia {
-    use crate::{from_screen_pixel_julia, SpaceParams};
-    use num_traits::Float;
 
-    pub fn calc_screen_space<F>(
-        x_bounds: (F, F),
-        y_bounds: (F, F),
-        resolution: (i32, i32),
+    pub fn calc_screen_space_smooth<F>(
+        viewport: Viewport<F>,
         c: (F, F),
-    ) -> impl Iterator<Item = (u64, bool)>
+        exponent: F,
+        params: RenderParams<F>,
+    ) -> impl ParallelIterator<Item = Option<F>>
     where
-        F: Float,
+        F: Float + Send + Sync,
+    {
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::calc_screen_space_smooth(formula, viewport, params)
+    }
+
+    /// Renders into a caller provided buffer, see [`crate::render_into`].
+    pub fn render_into<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
     {
-        let sp = SpaceParams::<F>::calc_space_params(x_bounds, y_bounds, resolution);
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::render_into(formula, buffer, stride, viewport, params, map)
+    }
 
-        (0i32..(resolution.0 * resolution.1))
-            .map(move |index| from_screen_pixel_julia(index, resolution, sp, c))
+    /// Renders smooth iteration counts into a caller provided buffer, see
+    /// [`crate::render_into`].
+    pub fn render_into_smooth<F, P, M>(
+        buffer: &mut [P],
+        stride: usize,
+        viewport: Viewport<F>,
+        c: (F, F),
+        exponent: F,
+        params: RenderParams<F>,
+        map: M,
+    ) where
+        F: Float + Send + Sync,
+        P: Send,
+        M: Fn(Option<F>) -> P + Send + Sync,
+    {
+        let formula = MultiJulia::new(Complex::new(c.0, c.1), exponent);
+        crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
     }
 }
 
+macro_rules! escape_time_modules {
+    ($(#[$meta:meta])* $module:ident, $formula:ident, $(#[$julia_meta:meta])* $julia_module:ident, $julia:ident) => {
+        $(#[$meta])*
+        #[cfg(not(feature = "parallel"))]
+        pub mod $module {
+            use crate::formula::$formula;
+            use crate::viewport::Viewport;
+            use crate::{IterationResult, RenderParams};
+            use num_complex::Complex;
+            use num_traits::Float;
+
+            pub fn calc_screen_space<F>(
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+            ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+            where
+                F: Float,
+            {
+                crate::calc_screen_space($formula, viewport, params)
+            }
+
+            pub fn calc_screen_space_smooth<F>(
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+            ) -> impl Iterator<Item = Option<F>>
+            where
+                F: Float,
+            {
+                crate::calc_screen_space_smooth($formula, viewport, params)
+            }
+
+            /// Renders into a caller provided buffer, see [`crate::render_into`].
+            pub fn render_into<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float,
+                M: Fn(IterationResult<Complex<F>>) -> P,
+            {
+                crate::render_into($formula, buffer, stride, viewport, params, map)
+            }
+
+            /// Renders smooth iteration counts into a caller provided buffer, see
+            /// [`crate::render_into`].
+            pub fn render_into_smooth<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float,
+                M: Fn(Option<F>) -> P,
+            {
+                crate::render_into_smooth($formula, buffer, stride, viewport, params, map)
+            }
+        }
+
+        $(#[$meta])*
+        #[cfg(feature = "parallel")]
+        pub mod $module {
+            use crate::formula::$formula;
+            use crate::viewport::Viewport;
+            use crate::{IterationResult, RenderParams};
+            use num_complex::Complex;
+            use num_traits::Float;
+            use rayon::prelude::*;
+
+            pub fn calc_screen_space<F>(
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+            ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+            where
+                F: Float + Send + Sync,
+            {
+                crate::calc_screen_space($formula, viewport, params)
+            }
+
+            pub fn calc_screen_space_smooth<F>(
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+            ) -> impl ParallelIterator<Item = Option<F>>
+            where
+                F: Float + Send + Sync,
+            {
+                crate::calc_screen_space_smooth($formula, viewport, params)
+            }
+
+            /// Renders into a caller provided buffer, see [`crate::render_into`].
+            pub fn render_into<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float + Send + Sync,
+                P: Send,
+                M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
+            {
+                crate::render_into($formula, buffer, stride, viewport, params, map)
+            }
+
+            /// Renders smooth iteration counts into a caller provided buffer, see
+            /// [`crate::render_into`].
+            pub fn render_into_smooth<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float + Send + Sync,
+                P: Send,
+                M: Fn(Option<F>) -> P + Send + Sync,
+            {
+                crate::render_into_smooth($formula, buffer, stride, viewport, params, map)
+            }
+        }
+
+        $(#[$julia_meta])*
+        #[cfg(not(feature = "parallel"))]
+        pub mod $julia_module {
+            use crate::formula::$julia;
+            use crate::viewport::Viewport;
+            use crate::{IterationResult, RenderParams};
+            use num_complex::Complex;
+            use num_traits::Float;
+
+            pub fn calc_screen_space<F>(
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+            ) -> impl Iterator<Item = IterationResult<Complex<F>>>
+            where
+                F: Float,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::calc_screen_space(formula, viewport, params)
+            }
+
+            pub fn calc_screen_space_smooth<F>(
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+            ) -> impl Iterator<Item = Option<F>>
+            where
+                F: Float,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::calc_screen_space_smooth(formula, viewport, params)
+            }
+
+            /// Renders into a caller provided buffer, see [`crate::render_into`].
+            pub fn render_into<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float,
+                M: Fn(IterationResult<Complex<F>>) -> P,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::render_into(formula, buffer, stride, viewport, params, map)
+            }
+
+            /// Renders smooth iteration counts into a caller provided buffer, see
+            /// [`crate::render_into`].
+            pub fn render_into_smooth<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float,
+                M: Fn(Option<F>) -> P,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
+            }
+        }
+
+        $(#[$julia_meta])*
+        #[cfg(feature = "parallel")]
+        pub mod $julia_module {
+            use crate::formula::$julia;
+            use crate::viewport::Viewport;
+            use crate::{IterationResult, RenderParams};
+            use num_complex::Complex;
+            use num_traits::Float;
+            use rayon::prelude::*;
+
+            pub fn calc_screen_space<F>(
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+            ) -> impl ParallelIterator<Item = IterationResult<Complex<F>>>
+            where
+                F: Float + Send + Sync,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::calc_screen_space(formula, viewport, params)
+            }
+
+            pub fn calc_screen_space_smooth<F>(
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+            ) -> impl ParallelIterator<Item = Option<F>>
+            where
+                F: Float + Send + Sync,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::calc_screen_space_smooth(formula, viewport, params)
+            }
+
+            /// Renders into a caller provided buffer, see [`crate::render_into`].
+            pub fn render_into<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float + Send + Sync,
+                P: Send,
+                M: Fn(IterationResult<Complex<F>>) -> P + Send + Sync,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::render_into(formula, buffer, stride, viewport, params, map)
+            }
+
+            /// Renders smooth iteration counts into a caller provided buffer, see
+            /// [`crate::render_into`].
+            pub fn render_into_smooth<F, P, M>(
+                buffer: &mut [P],
+                stride: usize,
+                viewport: Viewport<F>,
+                c: (F, F),
+                params: RenderParams<F>,
+                map: M,
+            ) where
+                F: Float + Send + Sync,
+                P: Send,
+                M: Fn(Option<F>) -> P + Send + Sync,
+            {
+                let formula = $julia {
+                    c: Complex::new(c.0, c.1),
+                };
+                crate::render_into_smooth(formula, buffer, stride, viewport, params, map)
+            }
+        }
+    };
+}
+
+escape_time_modules!(
+    /// Rendering of the Burning Ship fractal, see [`formula::BurningShip`].
+    burning_ship,
+    BurningShip,
+    /// Rendering of Burning Ship Julia sets, see [`formula::BurningShipJulia`].
+    burning_ship_julia,
+    BurningShipJulia
+);
+
+escape_time_modules!(
+    /// Rendering of the Tricorn, see [`formula::Tricorn`].
+    tricorn,
+    Tricorn,
+    /// Rendering of Tricorn Julia sets, see [`formula::TricornJulia`].
+    tricorn_julia,
+    TricornJulia
+);
+
+escape_time_modules!(
+    /// Rendering of the Celtic fractal, see [`formula::Celtic`].
+    celtic,
+    Celtic,
+    /// Rendering of Celtic Julia sets, see [`formula::CelticJulia`].
+    celtic_julia,
+    CelticJulia
+);
+
+escape_time_modules!(
+    /// Rendering of the Buffalo fractal, see [`formula::Buffalo`].
+    buffalo,
+    Buffalo,
+    /// Rendering of Buffalo Julia sets, see [`formula::BuffaloJulia`].
+    buffalo_julia,
+    BuffaloJulia
+);
+
 #[derive(Copy, Clone, Debug)]
-struct SpaceParams<F>
+pub struct RenderParams<F>
 where
     F: Float,
 {
-    scale: (F, F),
-    offset: (F, F),
-    delta_x: F,
-    delta_y: F,
+    /// Escape radius; an orbit is considered unstable once `|z| > bailout`.
+    pub bailout: F,
+    /// Iteration limit after which a still bounded orbit is reported as stable.
+    pub max_iterations: u64,
+    /// Distance below which two orbit points are considered equal during cycle detection;
+    /// `None` compares them exactly.
+    pub cycle_epsilon: Option<F>,
+    /// Whether points recognized by [`Formula::is_known_interior`] skip iteration.
+    ///
+    /// Renderers needing the orbit of every pixel iterate known interior points regardless:
+    /// [`accumulator::calc_screen_space`], [`trap::calc_screen_space`],
+    /// [`interior::calc_screen_space`] and [`interior::render_into`].
+    pub interior_checks: bool,
 }
 
-impl<F> SpaceParams<F>
+impl<F> Default for RenderParams<F>
 where
     F: Float,
 {
-    fn calc_space_params(
-        x_bounds: (F, F),
-        y_bounds: (F, F),
-        resolution: (i32, i32),
-    ) -> SpaceParams<F> {
-        let scale = (x_bounds.1 - x_bounds.0, y_bounds.1 - y_bounds.0);
+    fn default() -> Self {
+        RenderParams {
+            bailout: F::from(2).unwrap(),
+            max_iterations: 1000,
+            cycle_epsilon: None,
+            interior_checks: true,
+        }
+    }
+}
 
-        let offset = (
-            (x_bounds.0 + x_bounds.1) / F::from(2).unwrap(),
-            (y_bounds.0 + y_bounds.1) / F::from(2).unwrap(),
-        );
+impl<F> RenderParams<F>
+where
+    F: Float,
+{
+    fn is_bounded(&self, z: &Complex<F>) -> bool {
+        z.norm_sqr() <= self.bailout * self.bailout
+    }
+
+    fn is_same_point(&self, a: &Complex<F>, b: &Complex<F>) -> bool {
+        match self.cycle_epsilon {
+            Some(epsilon) => (a - b).norm_sqr() <= epsilon * epsilon,
+            None => a == b,
+        }
+    }
+}
 
-        let delta_x = scale.0 / F::from(resolution.0).unwrap();
-        let delta_y = scale.1 / F::from(resolution.1).unwrap();
+/// Rectangle of pixels within a larger image, see [`calc_screen_space_tile`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tile {
+    /// Column of the top left pixel.
+    pub x: u32,
+    /// Row of the top left pixel.
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
 
-        SpaceParams {
-            scale,
-            offset,
-            delta_x,
-            delta_y,
+impl Tile {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Tile {
+            x,
+            y,
+            width,
+            height,
         }
     }
+
+    /// Splits an image of `resolution` into tiles of `size`, row by row; tiles at the right and
+    /// bottom edges are cropped to the image.
+    ///
+    /// Panics if `size` is empty.
+    pub fn split(resolution: (u32, u32), size: (u32, u32)) -> impl Iterator<Item = Tile> {
+        assert!(size.0 > 0 && size.1 > 0, "empty tile size {:?}", size);
+        let columns = div_round_up(u64::from(resolution.0), u64::from(size.0));
+        let rows = div_round_up(u64::from(resolution.1), u64::from(size.1));
+
+        (0..columns * rows).map(move |index| {
+            let x = (index % columns) as u32 * size.0;
+            let y = (index / columns) as u32 * size.1;
+            Tile::new(
+                x,
+                y,
+                size.0.min(resolution.0 - x),
+                size.1.min(resolution.1 - y),
+            )
+        })
+    }
+
+    /// Number of pixels in the tile.
+    pub fn pixel_count(&self) -> u64 {
+        pixel_count((self.width, self.height))
+    }
+
+    fn is_within(&self, resolution: (u32, u32)) -> bool {
+        u64::from(self.x) + u64::from(self.width) <= u64::from(resolution.0)
+            && u64::from(self.y) + u64::from(self.height) <= u64::from(resolution.1)
+    }
+
+    /// Index in the full image of the pixel at `index` within the tile.
+    fn image_index(&self, index: u64, resolution: (u32, u32)) -> u64 {
+        let x = u64::from(self.x) + index % u64::from(self.width);
+        let y = u64::from(self.y) + index / u64::from(self.width);
+        y * u64::from(resolution.0) + x
+    }
+}
+
+/// The float type of a view cannot tell its neighboring pixels apart, see [`check_precision`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PrecisionLoss<F> {
+    /// Distance between neighboring pixels along the x and y axes.
+    pub spacing: (F, F),
+    /// Smallest distance between distinct coordinates around the view along both axes.
+    pub resolution: (F, F),
+}
+
+impl<F> fmt::Display for PrecisionLoss<F>
+where
+    F: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "pixel spacing ({}, {}) is below the float resolution ({}, {})",
+            self.spacing.0, self.spacing.1, self.resolution.0, self.resolution.1
+        )
+    }
 }
 
-fn from_screen_pixel_mandelbrot<F>(
-    index: i32,
-    resolution: (i32, i32),
-    sp: SpaceParams<F>,
-) -> (u64, bool)
+impl<F> std::error::Error for PrecisionLoss<F> where F: fmt::Debug + fmt::Display {}
+
+/// Checks that `F` resolves neighboring pixels of the view, i.e. that the pixel spacing exceeds
+/// the rounding error of the pixel coordinates. Rendering a view failing the check produces
+/// blocks of identical pixels; a wider float type such as
+/// [`DoubleDouble`](double_double::DoubleDouble) should be used instead.
+pub fn check_precision<F>(viewport: Viewport<F>) -> Result<(), PrecisionLoss<F>>
+where
+    F: Float,
+{
+    let magnitude = |bounds: (F, F)| bounds.0.abs().max(bounds.1.abs());
+    let (x_bounds, y_bounds) = viewport.bounds();
+    let spacing = viewport.pixel_spacing();
+    let spacing = (spacing.0.abs(), spacing.1.abs());
+    let resolvable = (
+        magnitude(x_bounds) * F::epsilon(),
+        magnitude(y_bounds) * F::epsilon(),
+    );
+    if spacing.0 > resolvable.0 && spacing.1 > resolvable.1 {
+        Ok(())
+    } else {
+        Err(PrecisionLoss {
+            spacing,
+            resolution: resolvable,
+        })
+    }
+}
+
+fn from_screen_pixel<F, T>(
+    formula: &T,
+    index: u64,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> IterationResult<Complex<F>>
 where
     F: Float,
+    T: Formula<F>,
 {
-    let (x, y) = from_screen_point_to_cartesian(index, resolution, sp);
+    let (x, y) = from_screen_point_to_cartesian(index, viewport);
+    let point = Complex::new(x, y);
+
+    if params.interior_checks && formula.is_known_interior(point) {
+        return IterationResult {
+            value: formula.initial(point),
+            iterations: 0,
+            termination: Termination::KnownInterior,
+        };
+    }
+
+    iterate(formula, &(), point, params).0
+}
 
-    is_stable(
-        |c: Complex<F>| {
-            c.powu(2) + Complex::<F>::new(NumCast::from(x).unwrap(), NumCast::from(y).unwrap())
+/// Iterates the orbit of `point`, feeding every orbit value to `accumulator`.
+fn iterate<F, T, A>(
+    formula: &T,
+    accumulator: &A,
+    point: Complex<F>,
+    params: RenderParams<F>,
+) -> (IterationResult<Complex<F>>, A::State)
+where
+    F: Float,
+    T: Formula<F>,
+    A: Accumulator<F>,
+{
+    let initial = formula.initial(point);
+    let c = formula.addend(point);
+    let orbit = is_stable_by(
+        |(z, state)| {
+            let z = formula.step(z, point);
+            (z, accumulator.accumulate(state, z, c))
         },
-        Complex::<F>::new(F::zero(), F::zero()),
-        |f| f.re < F::infinity() && f.im < F::infinity(),
-        1000,
-    )
+        (initial, accumulator.init(initial, c)),
+        |(z, _)| formula.is_bounded(z, &params),
+        params.max_iterations,
+        |(a, _), (b, _)| params.is_same_point(a, b),
+    );
+    let (z, state) = orbit.value;
+
+    (orbit.with_value(z), state)
 }
 
-fn from_screen_pixel_julia<F>(
-    index: i32,
-    resolution: (i32, i32),
-    sp: SpaceParams<F>,
-    c_: (F, F),
-) -> (u64, bool)
+fn from_screen_point_to_cartesian<F>(index: u64, viewport: Viewport<F>) -> (F, F)
 where
     F: Float,
 {
-    let (x, y) = from_screen_point_to_cartesian(index, resolution, sp);
+    let width = u64::from(viewport.resolution().0);
+    let screen = (index % width, index / width);
+    let half = F::from(0.5).unwrap();
+    // sample at the pixel center
+    let point = viewport.to_plane((
+        F::from(screen.0).unwrap() + half,
+        F::from(screen.1).unwrap() + half,
+    ));
 
-    is_stable(
-        |c: Complex<F>| c.powu(2) + Complex::<F>::new(c_.0, c_.1),
-        Complex::<F>::new(NumCast::from(x).unwrap(), NumCast::from(y).unwrap()),
-        |f| f.re < F::infinity() && f.im < F::infinity(),
-        1000,
-    )
+    (point.re, point.im)
+}
+
+/// Writes `pixel` of every pixel index of an image of `resolution` into `buffer`, row by row
+/// with rows `stride` elements apart.
+#[cfg(not(feature = "parallel"))]
+fn fill_buffer<P, G>(buffer: &mut [P], stride: usize, resolution: (u32, u32), pixel: G)
+where
+    G: Fn(u64) -> P,
+{
+    let width = check_buffer(buffer.len(), stride, resolution);
+    if width == 0 {
+        return;
+    }
+    for (y, row) in buffer
+        .chunks_mut(stride)
+        .take(resolution.1 as usize)
+        .enumerate()
+    {
+        let start = y as u64 * u64::from(resolution.0);
+        for (x, value) in row[..width].iter_mut().enumerate() {
+            *value = pixel(start + x as u64);
+        }
+    }
+}
+
+/// Writes `pixel` of every pixel index of an image of `resolution` into `buffer`, row by row
+/// with rows `stride` elements apart.
+#[cfg(feature = "parallel")]
+fn fill_buffer<P, G>(buffer: &mut [P], stride: usize, resolution: (u32, u32), pixel: G)
+where
+    P: Send,
+    G: Fn(u64) -> P + Send + Sync,
+{
+    let width = check_buffer(buffer.len(), stride, resolution);
+    if width == 0 {
+        return;
+    }
+    buffer
+        .par_chunks_mut(stride)
+        .take(resolution.1 as usize)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let start = y as u64 * u64::from(resolution.0);
+            for (x, value) in row[..width].iter_mut().enumerate() {
+                *value = pixel(start + x as u64);
+            }
+        });
 }
 
-fn from_screen_point_to_cartesian<F>(
-    index: i32,
-    resolution: (i32, i32),
-    sp: SpaceParams<F>,
-) -> (F, F)
+/// Checks that a buffer of `len` elements holds an image of `resolution` with rows `stride`
+/// elements apart, returning the row width.
+fn check_buffer(len: usize, stride: usize, resolution: (u32, u32)) -> usize {
+    let (width, height) = (resolution.0 as usize, resolution.1 as usize);
+    assert!(
+        stride >= width,
+        "stride {} below row width {}",
+        stride,
+        width
+    );
+    let required = match height.checked_sub(1) {
+        Some(rows) => rows
+            .checked_mul(stride)
+            .and_then(|start| start.checked_add(width)),
+        None => Some(0),
+    };
+    assert!(
+        matches!(required, Some(required) if len >= required),
+        "buffer of {} elements cannot hold {:?} pixels with stride {}",
+        len,
+        resolution,
+        stride
+    );
+    width
+}
+
+/// Number of pixels of an image, computed in 64 bits so that it cannot overflow.
+fn pixel_count(resolution: (u32, u32)) -> u64 {
+    u64::from(resolution.0) * u64::from(resolution.1)
+}
+
+/// `a / b` rounded up, as `u64::div_ceil` is not available on older compilers.
+fn div_round_up(a: u64, b: u64) -> u64 {
+    a / b + (a % b).min(1)
+}
+
+/// Offset of a pixel center from the view center, `delta` being the pixel spacing.
+fn from_screen_point_to_offset<F>(index: u64, resolution: (u32, u32), delta: (F, F)) -> (F, F)
 where
     F: Float,
 {
-    let screen_x = index % resolution.0;
-    let screen_y = index / resolution.0;
+    let half = F::from(0.5).unwrap();
+    let screen_x = F::from(index % u64::from(resolution.0)).unwrap() + half;
+    let screen_y = F::from(index / u64::from(resolution.0)).unwrap() + half;
 
     // convert to cartesian
-    let x = F::from(screen_x).unwrap() - (F::from(resolution.0).unwrap() / F::from(2).unwrap());
-    let y = F::from(-screen_y).unwrap() + (F::from(resolution.1).unwrap() / F::from(2).unwrap());
+    let x = screen_x - (F::from(resolution.0).unwrap() / F::from(2).unwrap());
+    let y = (F::from(resolution.1).unwrap() / F::from(2).unwrap()) - screen_y;
 
-    // convert to destination space
-    let x = (x * sp.delta_x) + sp.offset.0;
-    let y = (y * sp.delta_y) + sp.offset.1;
-
-    (x, y)
+    (x * delta.0, y * delta.1)
 }
 
 #[cfg(test)]
 mod tests {
     use num_complex::Complex64;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
 
-    use crate::is_stable;
+    use crate::double_double::DoubleDouble;
+    use crate::formula::{
+        Buffalo, BuffaloJulia, BurningShip, BurningShipJulia, Celtic, CelticJulia, Formula, Julia,
+        Mandelbrot, Tricorn, TricornJulia,
+    };
+    use crate::viewport::{Aspect, Viewport};
+    use crate::{
+        buffalo, buffalo_julia, burning_ship, burning_ship_julia, calc_screen_space, celtic,
+        celtic_julia, check_precision, from_screen_point_to_offset, is_stable, is_stable_by, julia,
+        mandelbrot, multibrot, multijulia, pixel_count, render_into, tricorn, tricorn_julia,
+        IterationResult, RenderParams, Termination, Tile,
+    };
+
+    /// Viewport whose only pixel is sampled exactly at `(x, y)`.
+    pub(crate) fn single_pixel(x: f64, y: f64) -> Viewport<f64> {
+        Viewport::new(Complex64::new(x, y), 1.0, (1, 1))
+    }
 
     #[test]
     fn unstable_positive_integer() {
-        let (_, stable) = is_stable(
+        let result = is_stable(
             |q| q.powu(2),
             Complex64::new(2.0, 0.0),
             |s| s.re < 999999.0,
             50000,
         );
-        assert_eq!(stable, false);
+        assert!(!result.is_stable());
+        assert_eq!(result.termination, Termination::Escaped);
+        assert_eq!(result.value, Complex64::new(65536.0 * 65536.0, 0.0));
+        assert_eq!(result.iterations, 5);
     }
 
     #[test]
     fn stable_positive_float() {
-        let (_, stable) = is_stable(
+        let result = is_stable(
             |q| q.powu(2),
             Complex64::new(0.5, 0.0),
             |s| s.re < 999999.0,
             50000,
         );
-        assert_eq!(stable, true);
+        assert!(result.is_stable());
+        assert_eq!(result.termination, Termination::Periodic(1));
+        assert_eq!(result.value, Complex64::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn stable_until_max_iterations() {
+        let rotation = Complex64::from_polar(1.0, 1.0);
+        let result = is_stable(
+            |q| q * rotation,
+            Complex64::new(0.5, 0.0),
+            |s| s.norm() < 2.0,
+            10,
+        );
+        assert_eq!(result.termination, Termination::MaxIterations);
+        assert_eq!(result.iterations, 10);
+    }
+
+    #[test]
+    fn detects_period_two_cycle() {
+        let result = is_stable(|q| -q, Complex64::new(0.5, 0.0), |s| s.re < 999999.0, 50000);
+        assert_eq!(result.termination, Termination::Periodic(2));
+        assert!(result.iterations < 10);
+    }
+
+    #[test]
+    fn detects_converging_cycle_within_epsilon() {
+        let c = Complex64::new(-1.1, 0.0);
+        let step = |z: Complex64| z * z + c;
+        let bounded = |z: &Complex64| z.norm() <= 2.0;
+        let exact = is_stable(step, Complex64::new(0.0, 0.0), bounded, 20);
+        assert_eq!(exact.termination, Termination::MaxIterations);
+
+        let close = |a: &Complex64, b: &Complex64| (a - b).norm() < 1e-9;
+        let approx = is_stable_by(step, Complex64::new(0.0, 0.0), bounded, 1000, close);
+        assert_eq!(approx.period(), Some(2));
+        assert!(approx.iterations < 1000);
+    }
+
+    #[test]
+    fn detects_unresolvable_pixel_spacing() {
+        let viewport = Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (1000, 1000));
+        assert_eq!(check_precision(viewport), Ok(()));
+        let x = (1.0, 1.0 + 1e-14);
+        let error =
+            check_precision(Viewport::from_bounds(x, (0.0, 1e-14), (1000, 1000))).unwrap_err();
+        assert!(error.spacing.0 < error.resolution.0);
+        // the y axis near zero is still resolved
+        assert!(error.spacing.1 > error.resolution.1);
+
+        let x = (
+            DoubleDouble::from(1.0),
+            DoubleDouble::from(1.0) + DoubleDouble::from(1e-14),
+        );
+        let y = (DoubleDouble::from(0.0), DoubleDouble::from(1e-14));
+        assert_eq!(
+            check_precision(Viewport::from_bounds(x, y, (1000, 1000))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn bailout_radius_stops_escaping_orbit() {
+        let render = |bailout| {
+            let params = RenderParams {
+                bailout,
+                ..RenderParams::default()
+            };
+            mandelbrot::calc_screen_space(
+                Viewport::from_bounds((1.0, 1.5), (1.0, 1.5), (1, 1)),
+                params,
+            )
+            .collect::<Vec<_>>()[0]
+        };
+        let classic = render(2.0);
+        let huge = render(1e100);
+        assert!(!classic.is_stable() && !huge.is_stable());
+        assert!(classic.iterations < 5);
+        assert!(classic.magnitude() > 2.0);
+        assert!(classic.iterations < huge.iterations);
+    }
+
+    #[test]
+    fn max_iterations_limits_stable_orbit() {
+        let params = RenderParams {
+            max_iterations: 37,
+            ..RenderParams::default()
+        };
+        let result = julia::calc_screen_space(
+            Viewport::from_bounds((-0.1, 0.1), (-0.1, 0.1), (2, 2)),
+            (0.0, 0.0),
+            params,
+        )
+        .collect::<Vec<_>>();
+        assert!(result.iter().all(|r| r.is_stable() && r.iterations <= 37));
+    }
+
+    #[test]
+    fn smooth_iterations_between_integer_counts() {
+        let params = RenderParams::default();
+        let results = mandelbrot::calc_screen_space(
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (8, 8)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        let smooth = mandelbrot::calc_screen_space_smooth(
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (8, 8)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        assert!(smooth.iter().any(Option::is_none));
+        assert!(smooth.iter().any(Option::is_some));
+        for (result, smooth) in results.iter().zip(smooth) {
+            match smooth {
+                None => assert!(result.is_stable()),
+                Some(nu) => {
+                    let n = result.iterations as f64;
+                    assert!(nu > n - 0.5 && nu <= n + 1.0, "{} too far from {}", nu, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_iterations_need_degree_above_one() {
+        let result = IterationResult {
+            value: Complex64::new(3.0, 0.0),
+            iterations: 5,
+            termination: Termination::Escaped,
+        };
+        assert!(result.smooth_iterations(2.0, 2.0).is_some());
+        assert!(result.smooth_iterations(2.0, -2.0).is_some());
+        for &degree in &[1.0, 0.5, 0.0, -0.5, -1.0] {
+            assert_eq!(result.smooth_iterations(2.0, degree), None);
+        }
+    }
+
+    #[test]
+    fn smooth_iterations_need_bailout_above_one() {
+        let result = IterationResult {
+            value: Complex64::new(1.2, 0.0),
+            iterations: 5,
+            termination: Termination::Escaped,
+        };
+        assert!(result.smooth_iterations(1.1, 2.0).is_some());
+        for &bailout in &[1.0, 0.5, 0.0] {
+            assert_eq!(result.smooth_iterations(bailout, 2.0), None);
+        }
+    }
+
+    #[test]
+    fn custom_formula_renders_through_screen_space() {
+        struct Square;
+
+        impl Formula<f64> for Square {
+            fn initial(&self, point: Complex64) -> Complex64 {
+                point
+            }
+
+            fn step(&self, z: Complex64, _point: Complex64) -> Complex64 {
+                z * z
+            }
+        }
+
+        let params = RenderParams::default();
+        let custom = calc_screen_space(
+            Square,
+            Viewport::from_bounds((-2.0, 2.0), (-2.0, 2.0), (16, 16)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        let julia = julia::calc_screen_space(
+            Viewport::from_bounds((-2.0, 2.0), (-2.0, 2.0), (16, 16)),
+            (0.0, 0.0),
+            params,
+        )
+        .collect::<Vec<_>>();
+        assert_eq!(custom, julia);
+        assert!(custom.iter().any(|r| r.is_stable()));
+        assert!(custom.iter().any(|r| !r.is_stable()));
+    }
+
+    const VARIANT_SIZE: u32 = 24;
+    const VARIANT_C: (f64, f64) = (-0.2, 0.1);
+
+    fn variant_viewport() -> Viewport<f64> {
+        Viewport::from_bounds((-2.5, 1.5), (-2.0, 2.0), (VARIANT_SIZE, VARIANT_SIZE))
+    }
+
+    fn variant_julia_viewport() -> Viewport<f64> {
+        Viewport::from_bounds((-2.0, 2.0), (-2.0, 2.0), (VARIANT_SIZE, VARIANT_SIZE))
+    }
+
+    /// Checks that a variant image has both stable and escaping pixels and differs from the
+    /// quadratic map it folds.
+    fn assert_variant_image(
+        image: &[IterationResult<Complex64>],
+        quadratic: &[IterationResult<Complex64>],
+    ) {
+        assert!(image.iter().any(|r| r.is_stable()));
+        assert!(image.iter().any(|r| !r.is_stable()));
+        assert_ne!(image, quadratic);
+    }
+
+    /// Whether the escape times are mirrored at the real axis.
+    fn is_mirrored(image: &[IterationResult<Complex64>]) -> bool {
+        let rows = image.chunks(VARIANT_SIZE as usize).collect::<Vec<_>>();
+        let escape = |row: &[IterationResult<Complex64>]| {
+            row.iter()
+                .map(|r| (r.iterations, r.termination))
+                .collect::<Vec<_>>()
+        };
+        rows.iter()
+            .zip(rows.iter().rev())
+            .all(|(a, b)| escape(a) == escape(b))
+    }
+
+    fn render_variant<T>(formula: T, viewport: Viewport<f64>) -> Vec<IterationResult<Complex64>>
+    where
+        T: Formula<f64> + Send + Sync,
+    {
+        calc_screen_space(formula, viewport, RenderParams::default()).collect()
+    }
+
+    #[test]
+    fn burning_ship_renders() {
+        let image = burning_ship::calc_screen_space(variant_viewport(), RenderParams::default())
+            .collect::<Vec<_>>();
+        assert_variant_image(&image, &render_variant(Mandelbrot, variant_viewport()));
+        assert!(!is_mirrored(&image));
+        assert_eq!(image, render_variant(BurningShip, variant_viewport()));
+    }
+
+    #[test]
+    fn tricorn_renders() {
+        let image = tricorn::calc_screen_space(variant_viewport(), RenderParams::default())
+            .collect::<Vec<_>>();
+        assert_variant_image(&image, &render_variant(Mandelbrot, variant_viewport()));
+        // the Tricorn commutes with conjugation, mirroring its image at the real axis
+        assert!(is_mirrored(&image));
+        assert_eq!(image, render_variant(Tricorn, variant_viewport()));
+    }
+
+    #[test]
+    fn celtic_renders() {
+        let image = celtic::calc_screen_space(variant_viewport(), RenderParams::default())
+            .collect::<Vec<_>>();
+        assert_variant_image(&image, &render_variant(Mandelbrot, variant_viewport()));
+        // the Celtic map commutes with conjugation, mirroring its image at the real axis
+        assert!(is_mirrored(&image));
+        assert_eq!(image, render_variant(Celtic, variant_viewport()));
+    }
+
+    #[test]
+    fn buffalo_renders() {
+        let image = buffalo::calc_screen_space(variant_viewport(), RenderParams::default())
+            .collect::<Vec<_>>();
+        assert_variant_image(&image, &render_variant(Mandelbrot, variant_viewport()));
+        assert!(!is_mirrored(&image));
+        assert_eq!(image, render_variant(Buffalo, variant_viewport()));
+    }
+
+    #[test]
+    fn burning_ship_julia_renders() {
+        let image = burning_ship_julia::calc_screen_space(
+            variant_julia_viewport(),
+            VARIANT_C,
+            RenderParams::default(),
+        )
+        .collect::<Vec<_>>();
+        let c = Complex64::new(VARIANT_C.0, VARIANT_C.1);
+        assert_variant_image(
+            &image,
+            &render_variant(Julia { c }, variant_julia_viewport()),
+        );
+        assert_eq!(
+            image,
+            render_variant(BurningShipJulia { c }, variant_julia_viewport())
+        );
+    }
+
+    #[test]
+    fn tricorn_julia_renders() {
+        let image = tricorn_julia::calc_screen_space(
+            variant_julia_viewport(),
+            VARIANT_C,
+            RenderParams::default(),
+        )
+        .collect::<Vec<_>>();
+        let c = Complex64::new(VARIANT_C.0, VARIANT_C.1);
+        assert_variant_image(
+            &image,
+            &render_variant(Julia { c }, variant_julia_viewport()),
+        );
+        assert_eq!(
+            image,
+            render_variant(TricornJulia { c }, variant_julia_viewport())
+        );
+    }
+
+    #[test]
+    fn celtic_julia_renders() {
+        let image = celtic_julia::calc_screen_space(
+            variant_julia_viewport(),
+            VARIANT_C,
+            RenderParams::default(),
+        )
+        .collect::<Vec<_>>();
+        let c = Complex64::new(VARIANT_C.0, VARIANT_C.1);
+        assert_variant_image(
+            &image,
+            &render_variant(Julia { c }, variant_julia_viewport()),
+        );
+        assert_eq!(
+            image,
+            render_variant(CelticJulia { c }, variant_julia_viewport())
+        );
+    }
+
+    #[test]
+    fn buffalo_julia_renders() {
+        let image = buffalo_julia::calc_screen_space(
+            variant_julia_viewport(),
+            VARIANT_C,
+            RenderParams::default(),
+        )
+        .collect::<Vec<_>>();
+        let c = Complex64::new(VARIANT_C.0, VARIANT_C.1);
+        assert_variant_image(
+            &image,
+            &render_variant(Julia { c }, variant_julia_viewport()),
+        );
+        assert_eq!(
+            image,
+            render_variant(BuffaloJulia { c }, variant_julia_viewport())
+        );
+    }
+
+    #[test]
+    fn known_interior_skips_iteration() {
+        let render = |x: f64, y: f64, interior_checks| {
+            let params = RenderParams {
+                interior_checks,
+                ..RenderParams::default()
+            };
+            mandelbrot::calc_screen_space(single_pixel(x, y), params).collect::<Vec<_>>()[0]
+        };
+        for &(x, y) in &[(-0.1, 0.1), (0.2, 0.0), (-1.0, 0.0), (-1.2, 0.1)] {
+            let result = render(x, y, true);
+            assert_eq!(result.termination, Termination::KnownInterior);
+            assert_eq!(result.iterations, 0);
+            let result = render(x, y, false);
+            assert!(result.is_stable());
+            assert_ne!(result.termination, Termination::KnownInterior);
+        }
+        assert!(!render(0.3, 0.0, true).is_stable());
+        assert_ne!(
+            render(-0.12, 0.75, true).termination,
+            Termination::KnownInterior
+        );
+    }
+
+    #[test]
+    fn tiles_reassemble_full_image() {
+        let resolution = (23, 17);
+        let viewport = Viewport::from_bounds((-2.0, 1.0), (-1.2, 1.3), resolution);
+        let params = RenderParams::default();
+        let full: Vec<_> = julia::calc_screen_space(viewport, (-0.4, 0.6), params).collect();
+
+        let mut assembled = vec![None; full.len()];
+        let tiles: Vec<_> = Tile::split(resolution, (8, 5)).collect();
+        assert_eq!(tiles.len(), 3 * 4);
+        assert_eq!(tiles[11], Tile::new(16, 15, 7, 2));
+        for tile in tiles {
+            let pixels: Vec<_> =
+                julia::calc_screen_space_tile(viewport, (-0.4, 0.6), tile, params).collect();
+            assert_eq!(pixels.len() as u64, tile.pixel_count());
+            for (index, pixel) in pixels.into_iter().enumerate() {
+                let x = tile.x as usize + index % tile.width as usize;
+                let y = tile.y as usize + index / tile.width as usize;
+                let slot = &mut assembled[y * resolution.0 as usize + x];
+                assert!(slot.is_none());
+                *slot = Some(pixel);
+            }
+        }
+        let assembled: Vec<_> = assembled.into_iter().map(Option::unwrap).collect();
+        assert_eq!(assembled, full);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tile_outside_image_panics() {
+        let tile = Tile::new(10, 0, 8, 8);
+        let _ = mandelbrot::calc_screen_space_tile(
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (16, 16)),
+            tile,
+            RenderParams::default(),
+        )
+        .count();
+    }
+
+    #[test]
+    fn indexes_images_beyond_32_bits() {
+        let resolution = (u32::MAX, 100_000);
+        let last = pixel_count(resolution) - 1;
+        assert_eq!(last, 429_496_729_499_999);
+        assert_eq!(
+            from_screen_point_to_offset(last, resolution, (1.0, 1.0)),
+            (2_147_483_647.0, -49_999.5)
+        );
+
+        let tile = Tile::new(u32::MAX - 2, 99_998, 2, 2);
+        let corner: Vec<_> = mandelbrot::calc_screen_space_tile(
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), resolution),
+            tile,
+            RenderParams::default(),
+        )
+        .collect();
+        assert_eq!(corner.len(), 4);
+        assert!(corner.iter().all(|result| !result.is_stable()));
+    }
+
+    #[test]
+    fn samples_pixel_centers_symmetrically() {
+        // reports the sampled point itself
+        struct Sample;
+
+        impl Formula<f64> for Sample {
+            fn initial(&self, point: Complex64) -> Complex64 {
+                point
+            }
+
+            fn step(&self, z: Complex64, _point: Complex64) -> Complex64 {
+                z
+            }
+
+            fn is_bounded(&self, _z: &Complex64, _params: &RenderParams<f64>) -> bool {
+                false
+            }
+        }
+
+        let (x, y) = ((-2.0, 1.0), (-1.0, 1.0));
+        for &aspect in &[Aspect::Fit, Aspect::Fill, Aspect::Stretch] {
+            let viewport = Viewport::from_bounds_with(x, y, (30, 10), aspect);
+            let points: Vec<_> = calc_screen_space(Sample, viewport, RenderParams::default())
+                .map(|result| result.value)
+                .collect();
+            let (first, last) = (points[0], points[points.len() - 1]);
+            let ((x0, x1), (y0, y1)) = viewport.bounds();
+
+            // half a pixel inside the bounds on every side
+            let spacing = viewport.pixel_spacing();
+            assert!((first.re - x0 - spacing.0 / 2.0).abs() < 1e-12);
+            assert!((x1 - last.re - spacing.0 / 2.0).abs() < 1e-12);
+            assert!((y1 - first.im - spacing.1 / 2.0).abs() < 1e-12);
+            assert!((last.im - y0 - spacing.1 / 2.0).abs() < 1e-12);
+            assert!(((first + last) / 2.0 - viewport.center()).norm() < 1e-12);
+
+            let within = |inner: (f64, f64), outer: (f64, f64)| {
+                outer.0 <= inner.0 + 1e-12 && inner.1 <= outer.1 + 1e-12
+            };
+            match aspect {
+                Aspect::Fit => assert!(within(x, (x0, x1)) && within(y, (y0, y1))),
+                Aspect::Fill => assert!(within((x0, x1), x) && within((y0, y1), y)),
+                Aspect::Stretch => assert_eq!(((x0, x1), (y0, y1)), (x, y)),
+            }
+        }
+    }
+
+    #[test]
+    fn renders_into_strided_buffer() {
+        let viewport = Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (7, 5));
+        let params = RenderParams::default();
+        let expected: Vec<_> = mandelbrot::calc_screen_space(viewport, params)
+            .map(|result| result.iterations)
+            .collect();
+
+        // rows of 7 pixels padded to 10, the last row without padding
+        let mut buffer = vec![u64::MAX; 4 * 10 + 7];
+        mandelbrot::render_into(&mut buffer, 10, viewport, params, |result| {
+            result.iterations
+        });
+        for (y, row) in buffer.chunks(10).enumerate() {
+            assert_eq!(row[..7], expected[y * 7..(y + 1) * 7]);
+            assert!(row[7..].iter().all(|&padding| padding == u64::MAX));
+        }
+
+        // the buffer is overwritten when reused
+        let smooth: Vec<_> =
+            julia::calc_screen_space_smooth(viewport, (-0.4, 0.6), params).collect();
+        let mut buffer = vec![Some(-1.0); 35];
+        julia::render_into_smooth(&mut buffer, 7, viewport, (-0.4, 0.6), params, |smooth| {
+            smooth
+        });
+        assert_eq!(buffer, smooth);
+        julia::render_into_smooth(&mut buffer, 7, viewport, (-0.4, 0.6), params, |smooth| {
+            smooth
+        });
+        assert_eq!(buffer, smooth);
+    }
+
+    #[test]
+    fn every_module_renders_smooth_counts_into_buffer() {
+        let viewport = Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (7, 5));
+        let params = RenderParams::default();
+        let (c, exponent) = ((-0.4, 0.6), 3.0);
+        let mut buffer = vec![None; 35];
+
+        mandelbrot::render_into_smooth(&mut buffer, 7, viewport, params, |smooth| smooth);
+        let expected: Vec<_> = mandelbrot::calc_screen_space_smooth(viewport, params).collect();
+        assert_eq!(buffer, expected);
+
+        multibrot::render_into_smooth(&mut buffer, 7, viewport, exponent, params, |smooth| smooth);
+        let expected: Vec<_> =
+            multibrot::calc_screen_space_smooth(viewport, exponent, params).collect();
+        assert_eq!(buffer, expected);
+
+        multijulia::render_into_smooth(&mut buffer, 7, viewport, c, exponent, params, |smooth| {
+            smooth
+        });
+        let expected: Vec<_> =
+            multijulia::calc_screen_space_smooth(viewport, c, exponent, params).collect();
+        assert_eq!(buffer, expected);
+
+        let mut buffer = vec![0; 35];
+        multijulia::render_into(&mut buffer, 7, viewport, c, exponent, params, |result| {
+            result.iterations
+        });
+        let expected: Vec<_> = multijulia::calc_screen_space(viewport, c, exponent, params)
+            .map(|result| result.iterations)
+            .collect();
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn render_into_rejects_short_buffer() {
+        let viewport = Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (7, 5));
+        let mut buffer = vec![0; 4 * 10 + 6];
+        render_into(
+            crate::formula::Mandelbrot,
+            &mut buffer,
+            10,
+            viewport,
+            RenderParams::default(),
+            |result| result.iterations,
+        );
     }
 }
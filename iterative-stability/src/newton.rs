@@ -0,0 +1,425 @@
+use num_complex::Complex;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::formula::Formula;
+use crate::viewport::Viewport;
+use crate::{IterationResult, RenderParams};
+
+/// Polynomial with complex coefficients, stored in ascending order of powers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial<F> {
+    coefficients: Vec<Complex<F>>,
+}
+
+impl<F> Polynomial<F>
+where
+    F: Float,
+{
+    /// `coefficients[k]` is the coefficient of `z^k`.
+    pub fn new(coefficients: Vec<Complex<F>>) -> Self {
+        let mut coefficients = coefficients;
+        while coefficients.len() > 1
+            && coefficients.last() == Some(&Complex::new(F::zero(), F::zero()))
+        {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    /// Monic polynomial `(z - roots[0]) * (z - roots[1]) * ...`.
+    pub fn from_roots(roots: &[Complex<F>]) -> Self {
+        let mut coefficients = vec![Complex::new(F::one(), F::zero())];
+        for root in roots {
+            let mut next = vec![Complex::new(F::zero(), F::zero()); coefficients.len() + 1];
+            for (k, coefficient) in coefficients.iter().enumerate() {
+                next[k + 1] = next[k + 1] + coefficient;
+                next[k] = next[k] - coefficient * root;
+            }
+            coefficients = next;
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn coefficients(&self) -> &[Complex<F>] {
+        &self.coefficients
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    pub fn eval(&self, z: Complex<F>) -> Complex<F> {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Complex::new(F::zero(), F::zero()), |acc, c| acc * z + c)
+    }
+
+    pub fn derivative(&self) -> Self {
+        let coefficients = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(k, c)| c * F::from(k).unwrap())
+            .collect::<Vec<_>>();
+        if coefficients.is_empty() {
+            Polynomial::new(vec![Complex::new(F::zero(), F::zero())])
+        } else {
+            Polynomial::new(coefficients)
+        }
+    }
+
+    /// Approximates all complex roots with the Durand-Kerner method.
+    pub fn roots(&self) -> Vec<Complex<F>> {
+        let degree = self.degree();
+        if degree == 0 {
+            return Vec::new();
+        }
+        let leading = self.coefficients[degree];
+        let monic = Polynomial {
+            coefficients: self.coefficients.iter().map(|c| c / leading).collect(),
+        };
+        let seed = Complex::new(F::from(0.4).unwrap(), F::from(0.9).unwrap());
+        let mut roots = (0..degree).map(|k| seed.powu(k as u32)).collect::<Vec<_>>();
+        let tolerance = F::epsilon() * F::from(16).unwrap();
+        for _ in 0..1000 {
+            let mut change = F::zero();
+            for i in 0..degree {
+                let denominator = (0..degree)
+                    .filter(|&j| j != i)
+                    .fold(Complex::new(F::one(), F::zero()), |acc, j| {
+                        acc * (roots[i] - roots[j])
+                    });
+                let delta = monic.eval(roots[i]) / denominator;
+                roots[i] = roots[i] - delta;
+                change = change.max(delta.norm());
+            }
+            if change <= tolerance {
+                break;
+            }
+        }
+        roots
+    }
+}
+
+/// Newton's method `z -> z - a * p(z) / p'(z)` for polynomial `p`, starting at the pixel.
+///
+/// `damping` is the relaxation factor `a`; `1` gives the plain Newton fractal.
+#[derive(Clone, Debug)]
+pub struct Newton<F> {
+    polynomial: Polynomial<F>,
+    derivative: Polynomial<F>,
+    roots: Vec<Complex<F>>,
+    pub damping: Complex<F>,
+    /// Maximum distance between the final orbit value and a root for the pixel to be
+    /// attributed to that root's basin.
+    pub root_tolerance: F,
+}
+
+impl<F> Newton<F>
+where
+    F: Float,
+{
+    /// Roots of `polynomial` are approximated numerically for basin identification.
+    pub fn new(polynomial: Polynomial<F>) -> Self {
+        let roots = polynomial.roots();
+        Self::with_roots(polynomial, roots)
+    }
+
+    pub fn from_roots(roots: &[Complex<F>]) -> Self {
+        Self::with_roots(Polynomial::from_roots(roots), roots.to_vec())
+    }
+
+    fn with_roots(polynomial: Polynomial<F>, roots: Vec<Complex<F>>) -> Self {
+        Newton {
+            derivative: polynomial.derivative(),
+            polynomial,
+            roots,
+            damping: Complex::new(F::one(), F::zero()),
+            root_tolerance: F::epsilon().sqrt(),
+        }
+    }
+
+    pub fn roots(&self) -> &[Complex<F>] {
+        &self.roots
+    }
+
+    /// Index of the root closest to `z`, if it lies within `root_tolerance`.
+    pub fn root_index(&self, z: Complex<F>) -> Option<usize> {
+        self.roots
+            .iter()
+            .map(|root| (root - z).norm())
+            .enumerate()
+            .filter(|&(_, distance)| distance <= self.root_tolerance)
+            .fold(None, |best: Option<(usize, F)>, (i, distance)| match best {
+                Some((_, best_distance)) if best_distance <= distance => best,
+                _ => Some((i, distance)),
+            })
+            .map(|(i, _)| i)
+    }
+}
+
+impl<F> Formula<F> for Newton<F>
+where
+    F: Float,
+{
+    fn initial(&self, point: Complex<F>) -> Complex<F> {
+        point
+    }
+
+    fn step(&self, z: Complex<F>, _point: Complex<F>) -> Complex<F> {
+        z - self.damping * self.polynomial.eval(z) / self.derivative.eval(z)
+    }
+
+    fn is_bounded(&self, z: &Complex<F>, _params: &RenderParams<F>) -> bool {
+        z.re.is_finite() && z.im.is_finite()
+    }
+}
+
+/// Nova fractal `z -> z - a * p(z) / p'(z) + c` with `c` being the pixel, starting at `start`.
+#[derive(Clone, Debug)]
+pub struct Nova<F> {
+    polynomial: Polynomial<F>,
+    derivative: Polynomial<F>,
+    pub damping: Complex<F>,
+    pub start: Complex<F>,
+}
+
+impl<F> Nova<F>
+where
+    F: Float,
+{
+    pub fn new(polynomial: Polynomial<F>, damping: Complex<F>, start: Complex<F>) -> Self {
+        Nova {
+            derivative: polynomial.derivative(),
+            polynomial,
+            damping,
+            start,
+        }
+    }
+}
+
+impl<F> Formula<F> for Nova<F>
+where
+    F: Float,
+{
+    fn initial(&self, _point: Complex<F>) -> Complex<F> {
+        self.start
+    }
+
+    fn step(&self, z: Complex<F>, point: Complex<F>) -> Complex<F> {
+        z - self.damping * self.polynomial.eval(z) / self.derivative.eval(z) + point
+    }
+
+    fn is_bounded(&self, z: &Complex<F>, _params: &RenderParams<F>) -> bool {
+        z.re.is_finite() && z.im.is_finite()
+    }
+}
+
+/// Newton iteration outcome of a single pixel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NewtonResult<F> {
+    /// Index into [`Newton::roots`] of the root the orbit converged to.
+    pub root: Option<usize>,
+    pub result: IterationResult<Complex<F>>,
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F>(
+    newton: Newton<F>,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = NewtonResult<F>>
+where
+    F: Float,
+{
+    let basins = newton.clone();
+    crate::calc_screen_space(newton, viewport, params).map(move |result| NewtonResult {
+        root: basins.root_index(result.value),
+        result,
+    })
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F>(
+    newton: Newton<F>,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = NewtonResult<F>>
+where
+    F: Float + Send + Sync,
+{
+    let basins = newton.clone();
+    crate::calc_screen_space(newton, viewport, params).map(move |result| NewtonResult {
+        root: basins.root_index(result.value),
+        result,
+    })
+}
+
+/// Nova iteration outcome of a single pixel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NovaResult<F> {
+    /// Point the orbit converged to, if it ended in a fixed point. Longer cycles are reported
+    /// by [`IterationResult::period`].
+    pub fixed_point: Option<Complex<F>>,
+    pub result: IterationResult<Complex<F>>,
+}
+
+impl<F> From<IterationResult<Complex<F>>> for NovaResult<F>
+where
+    F: Float,
+{
+    fn from(result: IterationResult<Complex<F>>) -> Self {
+        NovaResult {
+            fixed_point: match result.period() {
+                Some(1) => Some(result.value),
+                _ => None,
+            },
+            result,
+        }
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space_nova<F>(
+    nova: Nova<F>,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = NovaResult<F>>
+where
+    F: Float,
+{
+    crate::calc_screen_space(nova, viewport, params).map(NovaResult::from)
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space_nova<F>(
+    nova: Nova<F>,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = NovaResult<F>>
+where
+    F: Float + Send + Sync,
+{
+    crate::calc_screen_space(nova, viewport, params).map(NovaResult::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use super::{calc_screen_space, calc_screen_space_nova, Newton, Nova, Polynomial};
+    use crate::formula::Formula;
+    use crate::tests::single_pixel;
+    use crate::viewport::Viewport;
+    use crate::RenderParams;
+
+    fn cube_roots_of_unity() -> Vec<Complex64> {
+        (0..3)
+            .map(|k| Complex64::from_polar(1.0, 2.0 * std::f64::consts::PI * k as f64 / 3.0))
+            .collect()
+    }
+
+    #[test]
+    fn polynomial_from_roots_evaluates_to_zero_at_roots() {
+        let roots = cube_roots_of_unity();
+        let polynomial = Polynomial::from_roots(&roots);
+        assert_eq!(polynomial.degree(), 3);
+        for root in roots {
+            assert!(polynomial.eval(root).norm() < 1e-12);
+        }
+        let derivative = polynomial.derivative();
+        assert!((derivative.eval(Complex64::new(2.0, 0.0)) - 12.0).norm() < 1e-12);
+    }
+
+    #[test]
+    fn durand_kerner_finds_roots() {
+        let one = Complex64::new(1.0, 0.0);
+        let zero = Complex64::new(0.0, 0.0);
+        let polynomial = Polynomial::new(vec![-one, zero, zero, one]);
+        let found = polynomial.roots();
+        assert_eq!(found.len(), 3);
+        for root in cube_roots_of_unity() {
+            assert!(found.iter().any(|r| (r - root).norm() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn pixels_converge_to_nearest_basin() {
+        let roots = cube_roots_of_unity();
+        let newton = Newton::from_roots(&roots);
+        assert_eq!(newton.root_index(newton.step(roots[1], roots[1])), Some(1));
+
+        let params = RenderParams {
+            cycle_epsilon: Some(1e-12),
+            ..RenderParams::default()
+        };
+        let results = calc_screen_space(
+            newton,
+            Viewport::from_bounds((-2.0, 2.0), (-2.0, 2.0), (16, 16)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        for root in 0..3 {
+            assert!(results.iter().any(|r| r.root == Some(root)));
+        }
+        assert!(results
+            .iter()
+            .filter(|r| r.root.is_some())
+            .all(|r| r.result.is_stable() && r.result.period() == Some(1)));
+    }
+
+    #[test]
+    fn damping_slows_convergence() {
+        let roots = cube_roots_of_unity();
+        let plain = Newton::from_roots(&roots);
+        let mut relaxed = Newton::from_roots(&roots);
+        relaxed.damping = Complex64::new(0.5, 0.0);
+        let count = |newton: &Newton<f64>| {
+            let mut z = Complex64::new(2.0, 0.5);
+            let mut n = 0;
+            while newton.root_index(z).is_none() {
+                z = newton.step(z, z);
+                n += 1;
+            }
+            n
+        };
+        assert!(count(&plain) < count(&relaxed));
+    }
+
+    #[test]
+    fn nova_adds_pixel_to_newton_step() {
+        let roots = cube_roots_of_unity();
+        let newton = Newton::from_roots(&roots);
+        let one = Complex64::new(1.0, 0.0);
+        let nova = Nova::new(Polynomial::from_roots(&roots), one, one);
+        let z = Complex64::new(0.7, -0.4);
+        let c = Complex64::new(0.1, 0.2);
+        assert_eq!(nova.initial(c), one);
+        assert!((nova.step(z, c) - (newton.step(z, z) + c)).norm() < 1e-15);
+    }
+
+    #[test]
+    fn nova_converges_outside_bailout() {
+        // z^3 - 1 from 1 at c = 1.5 settles on the real root of z^3 - 4.5 z^2 - 1, beyond |z| = 2
+        let (zero, one) = (Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0));
+        let cubic = Polynomial::new(vec![-one, zero, zero, one]);
+        let nova = Nova::new(cubic, one, one);
+        let params = RenderParams {
+            cycle_epsilon: Some(1e-12),
+            ..RenderParams::default()
+        };
+        let result = calc_screen_space_nova(nova.clone(), single_pixel(1.5, 0.0), params)
+            .collect::<Vec<_>>()[0];
+        assert_eq!(result.result.period(), Some(1));
+        let fixed_point = result.fixed_point.unwrap();
+        assert!((fixed_point - Complex64::new(4.548, 0.0)).norm() < 1e-3);
+        let c = Complex64::new(1.5, 0.0);
+        assert!((nova.step(fixed_point, c) - fixed_point).norm() < 1e-9);
+    }
+}
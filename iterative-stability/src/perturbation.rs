@@ -0,0 +1,589 @@
+use num_complex::Complex;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::formula::{Formula, Mandelbrot};
+use crate::viewport::{rotate, Viewport};
+use crate::{
+    from_screen_point_to_cartesian, from_screen_point_to_offset, is_stable_by, pixel_count,
+    IterationResult, RenderParams, Termination,
+};
+
+/// Mandelbrot orbit of a single point, iterated in full precision and stored rounded to `f64`.
+///
+/// The orbit ends once it escapes or after `max_iterations` steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceOrbit {
+    orbit: Vec<Complex<f64>>,
+}
+
+impl ReferenceOrbit {
+    pub fn new<F>(center: Complex<F>, params: &RenderParams<F>) -> Self
+    where
+        F: Float,
+    {
+        let mut z = Formula::<F>::initial(&Mandelbrot, center);
+        let mut orbit = vec![to_f64(z)];
+        while (orbit.len() as u64) <= params.max_iterations {
+            z = Mandelbrot.step(z, center);
+            orbit.push(to_f64(z));
+            if !Mandelbrot.is_bounded(&z, params) {
+                break;
+            }
+        }
+        ReferenceOrbit { orbit }
+    }
+
+    /// Orbit values, starting with the initial `0`.
+    pub fn orbit(&self) -> &[Complex<f64>] {
+        &self.orbit
+    }
+}
+
+/// Settings of the series approximation in [`calc_screen_space_series`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SeriesParams {
+    /// Number of terms of the series in the pixel offset.
+    pub terms: usize,
+    /// Maximum size of the last series term relative to the first one, anywhere in the view.
+    /// The series is advanced along the reference orbit only while it stays below this bound.
+    pub tolerance: f64,
+}
+
+impl Default for SeriesParams {
+    fn default() -> Self {
+        SeriesParams {
+            terms: 8,
+            tolerance: 1e-12,
+        }
+    }
+}
+
+/// Univariate series `delta_n = sum(A_k * delta_c^k)` of the pixel orbit offsets from the
+/// reference orbit, used to skip the first iterations of every pixel at once.
+///
+/// Coefficients are stored scaled by powers of the view radius to avoid overflow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesApproximation<D> {
+    coefficients: Vec<Complex<D>>,
+    radius: D,
+    skipped: u64,
+}
+
+impl<D> SeriesApproximation<D>
+where
+    D: Float,
+{
+    /// Advances the series along `reference` for pixel offsets up to `radius`.
+    pub fn new(reference: &ReferenceOrbit, radius: D, series: SeriesParams) -> Self {
+        let reference = reference.orbit();
+        if series.terms == 0 || radius.is_zero() {
+            return SeriesApproximation::none();
+        }
+        let zero = Complex::new(D::zero(), D::zero());
+        let tolerance = D::from(series.tolerance).unwrap();
+        let mut coefficients = vec![zero; series.terms];
+
+        let mut skipped = 0;
+
+        while skipped + 1 < reference.len() - 1 {
+            let two_z = lift::<D>(reference[skipped]) * D::from(2).unwrap();
+            let next = (0..series.terms)
+                .map(|k| {
+                    // coefficient of delta_c^(k + 1) in (2 Z + delta) delta + delta_c
+                    let square = (0..k).fold(zero, |acc, i| {
+                        acc + coefficients[i] * coefficients[k - 1 - i]
+                    });
+                    let linear = if k == 0 { radius } else { D::zero() };
+                    two_z * coefficients[k] + square + linear
+                })
+                .collect::<Vec<_>>();
+            let accurate = next[series.terms - 1].norm() <= tolerance * next[0].norm();
+            if !accurate {
+                break;
+            }
+            coefficients = next;
+            skipped += 1;
+        }
+
+        SeriesApproximation {
+            coefficients,
+            radius,
+            skipped: skipped as u64,
+        }
+    }
+
+    fn none() -> Self {
+        SeriesApproximation {
+            coefficients: Vec::new(),
+            radius: D::one(),
+            skipped: 0,
+        }
+    }
+
+    /// Number of iterations skipped by every pixel.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Offset from the reference orbit after [`SeriesApproximation::skipped`] iterations of
+    /// the pixel at `delta_c` from the reference point.
+    pub fn evaluate(&self, delta_c: Complex<D>) -> Complex<D> {
+        let u = delta_c / self.radius;
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Complex::new(D::zero(), D::zero()), |acc, c| (acc + c) * u)
+    }
+}
+
+/// Mandelbrot set rendered with perturbation theory: only the orbit of the view center is
+/// iterated in the precision of `F`, pixels iterate their offset from it in `D`.
+///
+/// Zoom depth is thus limited by the precision of `F` and by the exponent range of `D`, e.g.
+/// `f64` or [`ExtendedFloat`](crate::extended::ExtendedFloat), rather than by the `f64`
+/// mantissa. Glitched pixels, whose orbit gets closer to `0` than to the reference orbit, are
+/// detected and rebased onto the start of the reference orbit automatically.
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F, D>(
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = IterationResult<Complex<D>>>
+where
+    F: Float,
+    D: Float,
+{
+    let center = viewport.center();
+    let reference = ReferenceOrbit::new(center, &params);
+    let series = SeriesApproximation::none();
+
+    (0..pixel_count(viewport.resolution()))
+        .map(move |index| from_screen_pixel(&reference, &series, index, viewport, params))
+}
+
+/// Mandelbrot set rendered with perturbation theory: only the orbit of the view center is
+/// iterated in the precision of `F`, pixels iterate their offset from it in `D`.
+///
+/// Zoom depth is thus limited by the precision of `F` and by the exponent range of `D`, e.g.
+/// `f64` or [`ExtendedFloat`](crate::extended::ExtendedFloat), rather than by the `f64`
+/// mantissa. Glitched pixels, whose orbit gets closer to `0` than to the reference orbit, are
+/// detected and rebased onto the start of the reference orbit automatically.
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F, D>(
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = IterationResult<Complex<D>>>
+where
+    F: Float + Send + Sync,
+    D: Float + Send + Sync,
+{
+    let center = viewport.center();
+    let reference = ReferenceOrbit::new(center, &params);
+    let series = SeriesApproximation::none();
+
+    (0..pixel_count(viewport.resolution()))
+        .into_par_iter()
+        .map(move |index| from_screen_pixel(&reference, &series, index, viewport, params))
+}
+
+/// Like [`calc_screen_space`], but all pixels skip the iterations covered by a
+/// [`SeriesApproximation`] of their offsets from the reference orbit.
+///
+/// Returns the number of skipped iterations together with the pixels.
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space_series<F, D>(
+    viewport: Viewport<F>,
+    series: SeriesParams,
+    params: RenderParams<F>,
+) -> (u64, impl Iterator<Item = IterationResult<Complex<D>>>)
+where
+    F: Float,
+    D: Float,
+{
+    let center = viewport.center();
+    let reference = ReferenceOrbit::new(center, &params);
+    let series = SeriesApproximation::new(&reference, view_radius(viewport), series);
+
+    (
+        series.skipped(),
+        (0..pixel_count(viewport.resolution()))
+            .map(move |index| from_screen_pixel(&reference, &series, index, viewport, params)),
+    )
+}
+
+/// Like [`calc_screen_space`], but all pixels skip the iterations covered by a
+/// [`SeriesApproximation`] of their offsets from the reference orbit.
+///
+/// Returns the number of skipped iterations together with the pixels.
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space_series<F, D>(
+    viewport: Viewport<F>,
+    series: SeriesParams,
+    params: RenderParams<F>,
+) -> (
+    u64,
+    impl ParallelIterator<Item = IterationResult<Complex<D>>>,
+)
+where
+    F: Float + Send + Sync,
+    D: Float + Send + Sync,
+{
+    let center = viewport.center();
+    let reference = ReferenceOrbit::new(center, &params);
+    let series = SeriesApproximation::new(&reference, view_radius(viewport), series);
+
+    (
+        series.skipped(),
+        (0..pixel_count(viewport.resolution()))
+            .into_par_iter()
+            .map(move |index| from_screen_pixel(&reference, &series, index, viewport, params)),
+    )
+}
+
+/// Distance from the view center to its corners.
+fn view_radius<F, D>(viewport: Viewport<F>) -> D
+where
+    F: Float,
+    D: Float,
+{
+    let two = F::from(2).unwrap();
+    convert((viewport.width() / two).hypot(viewport.height() / two))
+}
+
+fn from_screen_pixel<F, D>(
+    reference: &ReferenceOrbit,
+    series: &SeriesApproximation<D>,
+    index: u64,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> IterationResult<Complex<D>>
+where
+    F: Float,
+    D: Float,
+{
+    let zero = Complex::new(D::zero(), D::zero());
+    let (x, y) = from_screen_point_to_cartesian(index, viewport);
+
+    if params.interior_checks && Mandelbrot.is_known_interior(Complex::new(x, y)) {
+        return IterationResult {
+            value: zero,
+            iterations: 0,
+            termination: Termination::KnownInterior,
+        };
+    }
+
+    // offset from the pixel spacing rather than from coordinate differences in F, the spacing
+    // keeps its full precision when converted to D
+    let spacing = viewport.pixel_spacing();
+    let spacing = (convert::<F, D>(spacing.0), convert::<F, D>(spacing.1));
+    let offset = from_screen_point_to_offset(index, viewport.resolution(), spacing);
+    let (dx, dy) = rotate(offset, convert::<F, D>(viewport.rotation()));
+    let delta_c = Complex::new(dx, dy);
+
+    let reference = reference.orbit();
+    let bailout = convert::<F, D>(params.bailout);
+    let epsilon = params.cycle_epsilon.map(convert::<F, D>);
+    let is_bounded = |&(m, delta): &(usize, Complex<D>)| {
+        (lift::<D>(reference[m]) + delta).norm_sqr() <= bailout * bailout
+    };
+    // pixels escaping within the skipped iterations are iterated from the start
+    let skipped = (series.skipped() as usize, series.evaluate(delta_c));
+    let start = if is_bounded(&skipped) {
+        skipped
+    } else {
+        (0, zero)
+    };
+    let orbit = is_stable_by(
+        |(m, delta)| step(reference, m, delta, delta_c),
+        start,
+        is_bounded,
+        params.max_iterations - start.0 as u64,
+        |&(m1, d1), &(m2, d2)| {
+            let (a, b) = (lift::<D>(reference[m1]) + d1, lift::<D>(reference[m2]) + d2);
+            match epsilon {
+                Some(epsilon) => (a - b).norm_sqr() <= epsilon * epsilon,
+                None => a == b,
+            }
+        },
+    );
+    let (m, delta) = orbit.value;
+
+    IterationResult {
+        value: lift::<D>(reference[m]) + delta,
+        iterations: orbit.iterations + start.0 as u64,
+        termination: orbit.termination,
+    }
+}
+
+/// Advances the offset `delta` of a pixel orbit from the `m`-th reference orbit value by one
+/// step of `z^2 + c`, `delta_c` being the offset of the pixel from the reference point.
+///
+/// Once the pixel orbit gets closer to `0` than to the reference orbit, the offset has lost its
+/// precision relative to the reference (a glitch); the pixel orbit value then becomes the new
+/// offset from the start of the reference orbit. The same happens when the reference orbit
+/// ends before the pixel orbit does.
+fn step<D>(
+    reference: &[Complex<f64>],
+    m: usize,
+    delta: Complex<D>,
+    delta_c: Complex<D>,
+) -> (usize, Complex<D>)
+where
+    D: Float,
+{
+    let delta = (lift::<D>(reference[m]) * D::from(2).unwrap() + delta) * delta + delta_c;
+    let m = m + 1;
+    let z = lift::<D>(reference[m]) + delta;
+    if z.norm_sqr() < delta.norm_sqr() || m == reference.len() - 1 {
+        (0, z)
+    } else {
+        (m, delta)
+    }
+}
+
+fn to_f64<F>(z: Complex<F>) -> Complex<f64>
+where
+    F: Float,
+{
+    Complex::new(z.re.to_f64().unwrap(), z.im.to_f64().unwrap())
+}
+
+fn lift<D>(z: Complex<f64>) -> Complex<D>
+where
+    D: Float,
+{
+    Complex::new(D::from(z.re).unwrap(), D::from(z.im).unwrap())
+}
+
+/// Converts between float types through their binary decomposition, which unlike a conversion
+/// through `f64` keeps exponents outside the `f64` range.
+fn convert<F, D>(x: F) -> D
+where
+    F: Float,
+    D: Float,
+{
+    if x.is_zero() || !x.is_finite() {
+        return D::from(x.to_f64().unwrap()).unwrap();
+    }
+    // integer_decode saturates exponents beyond the i16 range, so x is first moved next to one
+    // by its binary exponent, which is then applied in D
+    let shift = x.abs().log2().floor().to_i32().unwrap();
+    let (mantissa, exponent, sign) = scale(x, -shift).integer_decode();
+    scale(
+        D::from(f64::from(sign) * mantissa as f64).unwrap(),
+        i32::from(exponent) + shift,
+    )
+}
+
+/// `x * 2^exponent`, in two steps so that the power of two itself stays finite.
+fn scale<F>(x: F, exponent: i32) -> F
+where
+    F: Float,
+{
+    let two = F::from(2).unwrap();
+    let half = exponent / 2;
+    x * two.powi(half) * two.powi(exponent - half)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use num_traits::Float;
+
+    use super::{
+        calc_screen_space, calc_screen_space_series, convert, step, ReferenceOrbit,
+        SeriesApproximation, SeriesParams,
+    };
+    use crate::double_double::DoubleDouble;
+    use crate::extended::ExtendedFloat;
+    use crate::viewport::Viewport;
+    use crate::{mandelbrot, IterationResult, RenderParams};
+
+    fn dd(source: &str) -> DoubleDouble {
+        source.parse().unwrap()
+    }
+
+    /// Every pixel must agree on stability and all but 1% on the exact iteration count, the
+    /// rest being boundary pixels whose orbits amplify rounding differences.
+    fn assert_agree<F, D>(
+        expected: &[IterationResult<Complex<F>>],
+        actual: &[IterationResult<Complex<D>>],
+    ) {
+        assert_eq!(expected.len(), actual.len());
+        for (index, (a, b)) in expected.iter().zip(actual).enumerate() {
+            assert_eq!(a.is_stable(), b.is_stable(), "pixel {}", index);
+        }
+        let exact = expected
+            .iter()
+            .zip(actual)
+            .filter(|(a, b)| a.iterations == b.iterations)
+            .count();
+        assert!(exact * 100 >= expected.len() * 99, "{}", exact);
+    }
+
+    #[test]
+    fn reference_orbit_stops_at_escape() {
+        let params = RenderParams::default();
+        let orbit = ReferenceOrbit::new(Complex::new(1.0, 0.0), &params);
+        assert_eq!(orbit.orbit().len(), 4);
+        let orbit = ReferenceOrbit::new(Complex::new(-1.0, 0.0), &params);
+        assert_eq!(orbit.orbit().len() as u64, params.max_iterations + 1);
+    }
+
+    #[test]
+    fn matches_direct_rendering() {
+        let params = RenderParams::default();
+        let direct = mandelbrot::calc_screen_space(
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (32, 32)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        let perturbed = calc_screen_space::<_, f64>(
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (32, 32)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        assert_agree(&direct, &perturbed);
+    }
+
+    #[test]
+    fn matches_direct_rendering_after_reference_escapes() {
+        let params = RenderParams::default();
+        // the reference orbit of the view center 0.5 escapes after a few iterations, so every
+        // pixel keeps being rebased onto its start
+        let viewport = Viewport::from_bounds((-1.5, 2.5), (-2.0, 2.0), (32, 32));
+        let reference = ReferenceOrbit::new(viewport.center(), &params);
+        assert!(reference.orbit().len() < 10, "{}", reference.orbit().len());
+
+        let direct = mandelbrot::calc_screen_space(viewport, params).collect::<Vec<_>>();
+        let perturbed = calc_screen_space::<_, f64>(viewport, params).collect::<Vec<_>>();
+        assert!(direct.iter().any(|r| r.is_stable()));
+        assert!(direct.iter().any(|r| r.iterations > 50 && !r.is_stable()));
+        assert_agree(&direct, &perturbed);
+    }
+
+    #[test]
+    fn resolves_zoom_beyond_f64() {
+        let params = RenderParams {
+            max_iterations: 5000,
+            ..RenderParams::default()
+        };
+        // Misiurewicz point c = i, the boundary has structure at every scale around it
+        let x = DoubleDouble::from(0.0);
+        let y = DoubleDouble::from(1.0);
+        let half = dd("1e-24");
+        let bounds = ((x - half, x + half), (y - half, y + half));
+
+        let direct = mandelbrot::calc_screen_space(
+            Viewport::from_bounds(bounds.0, bounds.1, (8, 8)),
+            params,
+        )
+        .collect::<Vec<IterationResult<Complex<DoubleDouble>>>>();
+        let perturbed =
+            calc_screen_space::<_, f64>(Viewport::from_bounds(bounds.0, bounds.1, (8, 8)), params)
+                .collect::<Vec<_>>();
+
+        let mut iterations = perturbed.iter().map(|r| r.iterations).collect::<Vec<_>>();
+        iterations.dedup();
+        assert!(iterations.len() > 1);
+        assert_agree(&direct, &perturbed);
+    }
+
+    #[test]
+    fn series_matches_iterated_offsets() {
+        let params = RenderParams::default();
+        let reference = ReferenceOrbit::new(Complex::new(-0.75, 0.1), &params);
+        let series = SeriesApproximation::new(
+            &reference,
+            1e-6,
+            SeriesParams {
+                terms: 4,
+                tolerance: 1e-6,
+            },
+        );
+        assert!(series.skipped() > 0);
+
+        let delta_c = Complex::new(3e-7, -5e-7);
+        let (m, delta) = (0..series.skipped())
+            .fold((0, Complex::new(0.0, 0.0)), |(m, delta), _| {
+                step(reference.orbit(), m, delta, delta_c)
+            });
+        assert_eq!(m as u64, series.skipped());
+        let error = (series.evaluate(delta_c) - delta).norm();
+        assert!(error <= 1e-6 * delta.norm(), "{} {}", error, delta.norm());
+    }
+
+    #[test]
+    fn series_skip_keeps_deep_zoom_results() {
+        let params = RenderParams::default();
+        let y = DoubleDouble::from(1.0);
+        let x = DoubleDouble::from(0.0);
+        let half = dd("1e-24");
+        let bounds = ((x - half, x + half), (y - half, y + half));
+
+        let plain =
+            calc_screen_space::<_, f64>(Viewport::from_bounds(bounds.0, bounds.1, (8, 8)), params)
+                .collect::<Vec<_>>();
+        let (skipped, series) = calc_screen_space_series::<_, f64>(
+            Viewport::from_bounds(bounds.0, bounds.1, (8, 8)),
+            SeriesParams::default(),
+            params,
+        );
+        let series = series.collect::<Vec<_>>();
+        assert!(skipped > 10, "{}", skipped);
+        assert_agree(&plain, &series);
+
+        let loose = SeriesParams {
+            tolerance: 1e-3,
+            ..SeriesParams::default()
+        };
+        let (loose_skipped, _) = calc_screen_space_series::<_, f64>(
+            Viewport::from_bounds(bounds.0, bounds.1, (8, 8)),
+            loose,
+            params,
+        );
+        assert!(loose_skipped >= skipped);
+    }
+
+    #[test]
+    fn converts_beyond_f64_exponent_range() {
+        for source in &["-3e-2000", "7e-20000", "-5e30000"] {
+            let x: ExtendedFloat = source.parse().unwrap();
+            let converted = convert::<ExtendedFloat, ExtendedFloat>(x);
+            assert!(
+                ((converted - x) / x).abs() < ExtendedFloat::from(1e-15),
+                "{}",
+                source
+            );
+        }
+        let tiny: ExtendedFloat = "1e-20000".parse().unwrap();
+        assert_eq!(convert::<ExtendedFloat, f64>(tiny), 0.0);
+        assert_eq!(convert::<f32, f64>(1e-45), f64::from(1e-45f32));
+        assert_eq!(convert::<DoubleDouble, f64>(DoubleDouble::from(0.1)), 0.1);
+        assert_eq!(convert::<f64, ExtendedFloat>(1e-310).exponent(), -1029);
+    }
+
+    #[test]
+    fn extended_offsets_match_f64_offsets() {
+        let params = RenderParams::default();
+        let half = dd("1e-24");
+        let (x, y) = (DoubleDouble::from(0.0), DoubleDouble::from(1.0));
+        let bounds = ((x - half, x + half), (y - half, y + half));
+
+        let plain =
+            calc_screen_space::<_, f64>(Viewport::from_bounds(bounds.0, bounds.1, (8, 8)), params)
+                .collect::<Vec<_>>();
+        let extended = calc_screen_space::<_, ExtendedFloat>(
+            Viewport::from_bounds(bounds.0, bounds.1, (8, 8)),
+            params,
+        )
+        .collect::<Vec<_>>();
+        for (a, b) in plain.iter().zip(&extended) {
+            assert_eq!((a.iterations, a.termination), (b.iterations, b.termination));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use num_complex::Complex;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::accumulator::{self, Accumulated, Accumulator};
+use crate::formula::Formula;
+use crate::viewport::Viewport;
+use crate::{IterationResult, RenderParams};
+
+/// A shape orbits are measured against.
+pub trait Trap<F>
+where
+    F: Float,
+{
+    /// Distance from `z` to the trap.
+    fn distance(&self, z: Complex<F>) -> F;
+}
+
+/// Built-in trap shapes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TrapShape<F> {
+    Point(Complex<F>),
+    /// Infinite line through `point` in the direction of `direction`.
+    Line {
+        point: Complex<F>,
+        direction: Complex<F>,
+    },
+    /// Pair of axis-aligned lines crossing at the given point.
+    Cross(Complex<F>),
+    Circle {
+        center: Complex<F>,
+        radius: F,
+    },
+}
+
+impl<F> Trap<F> for TrapShape<F>
+where
+    F: Float,
+{
+    fn distance(&self, z: Complex<F>) -> F {
+        match *self {
+            TrapShape::Point(point) => (z - point).norm(),
+            TrapShape::Line { point, direction } => {
+                // imaginary part of the offset rotated onto the line direction
+                ((z - point) * direction.conj()).im.abs() / direction.norm()
+            }
+            TrapShape::Cross(center) => {
+                let offset = z - center;
+                offset.re.abs().min(offset.im.abs())
+            }
+            TrapShape::Circle { center, radius } => ((z - center).norm() - radius).abs(),
+        }
+    }
+}
+
+/// Escape data of a single pixel together with the closest approach of its orbit to a trap.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TrapResult<F> {
+    /// Minimum distance between the orbit and the trap.
+    pub distance: F,
+    /// Iteration at which the minimum distance occurred, `1` being the first iterate.
+    pub iteration: u64,
+    pub result: IterationResult<Complex<F>>,
+}
+
+/// Closest approach of the orbit to a trap, as an [`Accumulator`] yielding the minimum distance
+/// and the iteration at which it occurred.
+///
+/// Only iterates are measured: the initial value is the same for every Mandelbrot pixel and
+/// would flatten traps around it.
+#[derive(Copy, Clone, Debug)]
+pub struct TrapAccumulator<S>(pub S);
+
+impl<F, S> Accumulator<F> for TrapAccumulator<S>
+where
+    F: Float,
+    S: Trap<F>,
+{
+    /// Iteration of the latest orbit value, closest distance so far and its iteration.
+    type State = (u64, F, u64);
+    type Output = (F, u64);
+
+    fn init(&self, _z: Complex<F>, _c: Option<Complex<F>>) -> Self::State {
+        (0, F::infinity(), 0)
+    }
+
+    fn accumulate(&self, state: Self::State, z: Complex<F>, _c: Option<Complex<F>>) -> Self::State {
+        let (i, closest, closest_at) = state;
+        let distance = self.0.distance(z);
+        if distance < closest {
+            (i + 1, distance, i + 1)
+        } else {
+            (i + 1, closest, closest_at)
+        }
+    }
+
+    fn finish(
+        &self,
+        state: Self::State,
+        _result: &IterationResult<Complex<F>>,
+        _smooth: Option<F>,
+    ) -> Self::Output {
+        (state.1, state.2)
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn calc_screen_space<F, T, S>(
+    formula: T,
+    trap: S,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl Iterator<Item = TrapResult<F>>
+where
+    F: Float,
+    T: Formula<F>,
+    S: Trap<F>,
+{
+    let accumulator = TrapAccumulator(trap);
+    accumulator::calc_screen_space(formula, accumulator, viewport, params).map(TrapResult::from)
+}
+
+#[cfg(feature = "parallel")]
+pub fn calc_screen_space<F, T, S>(
+    formula: T,
+    trap: S,
+    viewport: Viewport<F>,
+    params: RenderParams<F>,
+) -> impl ParallelIterator<Item = TrapResult<F>>
+where
+    F: Float + Send + Sync,
+    T: Formula<F> + Send + Sync,
+    S: Trap<F> + Send + Sync,
+{
+    let accumulator = TrapAccumulator(trap);
+    accumulator::calc_screen_space(formula, accumulator, viewport, params).map(TrapResult::from)
+}
+
+impl<F> From<Accumulated<F, (F, u64)>> for TrapResult<F> {
+    fn from(accumulated: Accumulated<F, (F, u64)>) -> Self {
+        let (distance, iteration) = accumulated.value;
+        TrapResult {
+            distance,
+            iteration,
+            result: accumulated.result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    use super::{calc_screen_space, Trap, TrapShape};
+    use crate::formula::{Julia, Mandelbrot};
+    use crate::tests::single_pixel;
+    use crate::viewport::Viewport;
+    use crate::RenderParams;
+
+    #[test]
+    fn shape_distances() {
+        let z = Complex64::new(3.0, 4.0);
+        let origin = Complex64::new(0.0, 0.0);
+        assert_eq!(TrapShape::Point(origin).distance(z), 5.0);
+        let diagonal = TrapShape::Line {
+            point: origin,
+            direction: Complex64::new(2.0, 2.0),
+        };
+        assert!((diagonal.distance(z) - 0.5f64.sqrt()).abs() < 1e-12);
+        assert_eq!(TrapShape::Cross(Complex64::new(1.0, 1.0)).distance(z), 2.0);
+        let circle = TrapShape::Circle {
+            center: origin,
+            radius: 2.0,
+        };
+        assert_eq!(circle.distance(z), 3.0);
+    }
+
+    #[test]
+    fn records_closest_approach() {
+        // orbit of 0.5 under z^2: 0.5, 0.25, 0.0625, ...
+        let julia = Julia {
+            c: Complex64::new(0.0, 0.0),
+        };
+        let trap = TrapShape::Point(Complex64::new(0.3, 0.0));
+        let result =
+            calc_screen_space(julia, trap, single_pixel(0.5, 0.0), RenderParams::default())
+                .collect::<Vec<_>>()[0];
+        assert_eq!(result.iteration, 1);
+        assert!((result.distance - 0.05).abs() < 1e-9);
+        assert!(result.result.is_stable());
+    }
+
+    #[test]
+    fn traps_around_mandelbrot_start_vary() {
+        // every Mandelbrot orbit starts at the origin, so a trap there must not count it
+        let trap = TrapShape::Point(Complex64::new(0.0, 0.0));
+        let distances = calc_screen_space(
+            Mandelbrot,
+            trap,
+            Viewport::from_bounds((-2.0, 1.0), (-1.5, 1.5), (16, 16)),
+            RenderParams::default(),
+        )
+        .map(|result| result.distance)
+        .collect::<Vec<_>>();
+        assert!(distances.iter().all(|&distance| distance > 0.0));
+        assert!(distances.iter().any(|&distance| distance != distances[0]));
+    }
+}
@@ -0,0 +1,297 @@
+use num_complex::Complex;
+use num_traits::Float;
+
+/// How bounds whose aspect ratio differs from the one of the image are mapped onto it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aspect {
+    /// The bounds are shown entirely and extended along one axis, keeping pixels square.
+    Fit,
+    /// The bounds cover the whole image and are cropped along one axis, keeping pixels square.
+    Fill,
+    /// The bounds are mapped exactly onto the image, stretching pixels.
+    Stretch,
+}
+
+/// Region of the complex plane shown by an image of `resolution` pixels.
+///
+/// The view is described by its center, its extent along both image axes and a counterclockwise
+/// rotation of those axes. Pixel coordinates grow to the right and downwards from the top left
+/// corner of the image, pixel `(i, j)` covering `[i, i + 1) x [j, j + 1)`; renderers sample
+/// every pixel at its center.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport<F> {
+    center: Complex<F>,
+    width: F,
+    height: F,
+    rotation: F,
+    resolution: (u32, u32),
+}
+
+impl<F> Viewport<F>
+where
+    F: Float,
+{
+    /// View of the given plane `width` around `center`, the height following from the aspect
+    /// ratio of `resolution` so that pixels are square.
+    pub fn new(center: Complex<F>, width: F, resolution: (u32, u32)) -> Self {
+        Viewport {
+            center,
+            width,
+            height: width * aspect_ratio(resolution),
+            rotation: F::zero(),
+            resolution,
+        }
+    }
+
+    /// View spanning exactly `x_bounds` and `y_bounds`, stretching the plane if their ratio
+    /// differs from the one of `resolution`.
+    pub fn from_bounds(x_bounds: (F, F), y_bounds: (F, F), resolution: (u32, u32)) -> Self {
+        Viewport::from_bounds_with(x_bounds, y_bounds, resolution, Aspect::Stretch)
+    }
+
+    /// View of `x_bounds` and `y_bounds` centered in an image of `resolution`, their aspect
+    /// ratio adapted as given by `aspect`.
+    pub fn from_bounds_with(
+        x_bounds: (F, F),
+        y_bounds: (F, F),
+        resolution: (u32, u32),
+        aspect: Aspect,
+    ) -> Self {
+        let two = F::from(2).unwrap();
+        let (mut width, mut height) = (x_bounds.1 - x_bounds.0, y_bounds.1 - y_bounds.0);
+        let pixels = (
+            F::from(resolution.0).unwrap(),
+            F::from(resolution.1).unwrap(),
+        );
+        let spacing = ((width / pixels.0).abs(), (height / pixels.1).abs());
+        let square = match aspect {
+            Aspect::Fit => Some(spacing.0.max(spacing.1)),
+            Aspect::Fill => Some(spacing.0.min(spacing.1)),
+            Aspect::Stretch => None,
+        };
+        if let Some(spacing) = square {
+            width = width.signum() * spacing * pixels.0;
+            height = height.signum() * spacing * pixels.1;
+        }
+
+        Viewport {
+            center: Complex::new(
+                (x_bounds.0 + x_bounds.1) / two,
+                (y_bounds.0 + y_bounds.1) / two,
+            ),
+            width,
+            height,
+            rotation: F::zero(),
+            resolution,
+        }
+    }
+
+    /// The same view rotated counterclockwise by `angle` radians around its center.
+    pub fn with_rotation(self, angle: F) -> Self {
+        Viewport {
+            rotation: angle,
+            ..self
+        }
+    }
+
+    /// The same view rendered at another resolution, keeping center and width; the height
+    /// follows the new aspect ratio as long as pixels were square.
+    pub fn with_resolution(self, resolution: (u32, u32)) -> Self {
+        let pixel_aspect = self.height / self.width / aspect_ratio(self.resolution);
+        Viewport {
+            height: self.width * aspect_ratio(resolution) * pixel_aspect,
+            resolution,
+            ..self
+        }
+    }
+
+    pub fn center(&self) -> Complex<F> {
+        self.center
+    }
+
+    /// Extent of the view along the image x axis.
+    pub fn width(&self) -> F {
+        self.width
+    }
+
+    /// Extent of the view along the image y axis.
+    pub fn height(&self) -> F {
+        self.height
+    }
+
+    /// Counterclockwise rotation in radians.
+    pub fn rotation(&self) -> F {
+        self.rotation
+    }
+
+    pub fn resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    /// Distance between neighboring pixels along the image x and y axes.
+    pub fn pixel_spacing(&self) -> (F, F) {
+        (
+            self.width / F::from(self.resolution.0).unwrap(),
+            self.height / F::from(self.resolution.1).unwrap(),
+        )
+    }
+
+    /// Axis aligned bounds of the view in the plane, covering all of it when rotated.
+    pub fn bounds(&self) -> ((F, F), (F, F)) {
+        let two = F::from(2).unwrap();
+        let (half_x, half_y) = rotate((self.width / two, self.height / two), self.rotation);
+        let (other_x, other_y) = rotate((self.width / two, -self.height / two), self.rotation);
+        let extent = (
+            half_x.abs().max(other_x.abs()),
+            half_y.abs().max(other_y.abs()),
+        );
+        (
+            (self.center.re - extent.0, self.center.re + extent.0),
+            (self.center.im - extent.1, self.center.im + extent.1),
+        )
+    }
+
+    /// Point of the plane at `pixel`.
+    pub fn to_plane(&self, pixel: (F, F)) -> Complex<F> {
+        let two = F::from(2).unwrap();
+        let spacing = self.pixel_spacing();
+        let x = pixel.0 - (F::from(self.resolution.0).unwrap() / two);
+        let y = (F::from(self.resolution.1).unwrap() / two) - pixel.1;
+        let (x, y) = rotate((x * spacing.0, y * spacing.1), self.rotation);
+
+        Complex::new(x + self.center.re, y + self.center.im)
+    }
+
+    /// Pixel coordinates of `point`, the inverse of [`Viewport::to_plane`].
+    pub fn to_pixel(&self, point: Complex<F>) -> (F, F) {
+        let two = F::from(2).unwrap();
+        let spacing = self.pixel_spacing();
+        let offset = point - self.center;
+        let (x, y) = rotate((offset.re, offset.im), -self.rotation);
+
+        (
+            x / spacing.0 + F::from(self.resolution.0).unwrap() / two,
+            F::from(self.resolution.1).unwrap() / two - y / spacing.1,
+        )
+    }
+
+    /// Magnifies the view by `factor` while the point at `pixel` stays in place; factors below
+    /// one zoom out.
+    pub fn zoom(self, pixel: (F, F), factor: F) -> Self {
+        let fixed = self.to_plane(pixel);
+        Viewport {
+            center: fixed + (self.center - fixed) / factor,
+            width: self.width / factor,
+            height: self.height / factor,
+            ..self
+        }
+    }
+
+    /// Moves the view so that the point at `pixels` from the center becomes the new center.
+    pub fn pan(self, pixels: (F, F)) -> Self {
+        let two = F::from(2).unwrap();
+        let center = self.to_plane((
+            F::from(self.resolution.0).unwrap() / two + pixels.0,
+            F::from(self.resolution.1).unwrap() / two + pixels.1,
+        ));
+        Viewport { center, ..self }
+    }
+}
+
+fn aspect_ratio<F>(resolution: (u32, u32)) -> F
+where
+    F: Float,
+{
+    F::from(resolution.1).unwrap() / F::from(resolution.0).unwrap()
+}
+
+/// Rotates `offset` counterclockwise by `angle` radians; a zero angle leaves it untouched.
+pub(crate) fn rotate<F>(offset: (F, F), angle: F) -> (F, F)
+where
+    F: Float,
+{
+    if angle.is_zero() {
+        return offset;
+    }
+    let (sin, cos) = angle.sin_cos();
+    (
+        offset.0 * cos - offset.1 * sin,
+        offset.0 * sin + offset.1 * cos,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex64;
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::{Aspect, Viewport};
+
+    fn assert_close(a: Complex64, b: Complex64) {
+        assert!((a - b).norm() < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn square_pixels_follow_resolution() {
+        let viewport = Viewport::new(Complex64::new(-0.5, 0.0), 3.0, (300, 200));
+        assert_eq!(viewport.height(), 2.0);
+        assert_eq!(viewport.pixel_spacing(), (0.01, 0.01));
+        assert_eq!(viewport.bounds(), ((-2.0, 1.0), (-1.0, 1.0)));
+
+        let resized = viewport.with_resolution((100, 100));
+        assert_eq!((resized.width(), resized.height()), (3.0, 3.0));
+    }
+
+    #[test]
+    fn pixels_round_trip_through_plane() {
+        let viewport = Viewport::new(Complex64::new(0.3, -0.2), 2.0, (64, 48)).with_rotation(0.7);
+        for &pixel in &[(0.0, 0.0), (63.0, 47.0), (12.5, 30.25)] {
+            let back = viewport.to_pixel(viewport.to_plane(pixel));
+            assert!((back.0 - pixel.0).abs() < 1e-9 && (back.1 - pixel.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rotation_turns_image_axes() {
+        let viewport =
+            Viewport::new(Complex64::new(0.0, 0.0), 2.0, (2, 2)).with_rotation(FRAC_PI_2);
+        // the right edge of the image points up in the plane
+        assert_close(viewport.to_plane((2.0, 1.0)), Complex64::new(0.0, 1.0));
+        let ((x0, x1), (y0, y1)) = viewport.bounds();
+        assert_close(Complex64::new(x0, y0), Complex64::new(-1.0, -1.0));
+        assert_close(Complex64::new(x1, y1), Complex64::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn zoom_keeps_point_under_pixel() {
+        let viewport = Viewport::new(Complex64::new(-0.5, 0.0), 3.0, (300, 300)).with_rotation(0.3);
+        let pixel = (75.0, 210.0);
+        let zoomed = viewport.zoom(pixel, 4.0);
+        assert_close(zoomed.to_plane(pixel), viewport.to_plane(pixel));
+        assert_eq!(zoomed.width(), 0.75);
+    }
+
+    #[test]
+    fn pan_moves_center_by_pixels() {
+        let viewport = Viewport::from_bounds((-2.0, 2.0), (-1.0, 1.0), (400, 200));
+        let panned = viewport.pan((100.0, -50.0));
+        assert_close(panned.center(), Complex64::new(1.0, 0.5));
+        assert_close(panned.to_plane((0.0, 0.0)), Complex64::new(-1.0, 1.5));
+    }
+
+    #[test]
+    fn aspect_modes_keep_pixels_square() {
+        let (x, y) = ((-2.0, 2.0), (-1.0, 1.0));
+        let fit = Viewport::from_bounds_with(x, y, (100, 100), Aspect::Fit);
+        assert_eq!((fit.width(), fit.height()), (4.0, 4.0));
+        let fill = Viewport::from_bounds_with(x, y, (100, 100), Aspect::Fill);
+        assert_eq!((fill.width(), fill.height()), (2.0, 2.0));
+        let stretch = Viewport::from_bounds_with(x, y, (100, 100), Aspect::Stretch);
+        assert_eq!(stretch, Viewport::from_bounds(x, y, (100, 100)));
+        assert_eq!((stretch.width(), stretch.height()), (4.0, 2.0));
+
+        // reversed bounds keep their orientation
+        let flipped = Viewport::from_bounds_with((2.0, -2.0), y, (100, 50), Aspect::Fill);
+        assert_eq!((flipped.width(), flipped.height()), (-4.0, 2.0));
+    }
+}
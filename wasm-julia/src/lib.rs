@@ -1,6 +1,7 @@
 mod utils;
 
-use iterative_stability::julia;
+use iterative_stability::viewport::Viewport;
+use iterative_stability::{julia, RenderParams};
 use palette::{Hsv, Hue, Srgb};
 use wasm_bindgen::prelude::*;
 
@@ -10,20 +11,61 @@ use wasm_bindgen::prelude::*;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+const SIZE: u32 = 1000;
+
+/// Julia set renderer keeping its pixel buffer between frames.
 #[wasm_bindgen]
-pub fn gen(palette_length: u32, palette_hue: f32, cx: f64, cy: f64) -> Vec<u32> {
-    julia::calc_screen_space((-2.0, 2.0), (-2.0, 2.0), (1000, 1000), (cx, cy))
-        .map(|(iter, stable)| apply_palette(iter, stable, palette_length, palette_hue))
-        .collect()
+pub struct Renderer {
+    pixels: Vec<u32>,
 }
 
-fn apply_palette(iter: u64, stable: bool, length: u32, hue: f32) -> u32 {
-    if stable {
-        0xff000000
-    } else {
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer {
+            pixels: vec![0; (SIZE * SIZE) as usize],
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    pub fn gen(
+        &mut self,
+        palette_length: u32,
+        palette_hue: f32,
+        cx: f64,
+        cy: f64,
+        max_iterations: u32,
+    ) {
+        let params = RenderParams {
+            max_iterations: max_iterations as u64,
+            ..RenderParams::default()
+        };
+        julia::render_into_smooth(
+            &mut self.pixels,
+            SIZE as usize,
+            Viewport::from_bounds((-2.0, 2.0), (-2.0, 2.0), (SIZE, SIZE)),
+            (cx, cy),
+            params,
+            |smooth_iter| apply_palette(smooth_iter, palette_length, palette_hue),
+        );
+    }
+
+    /// Location of the RGBA pixels in wasm memory, valid until the renderer is dropped.
+    pub fn pixels(&self) -> *const u32 {
+        self.pixels.as_ptr()
+    }
+}
+
+fn apply_palette(smooth_iter: Option<f64>, length: u32, hue: f32) -> u32 {
+    if let Some(iter) = smooth_iter {
         let hsv_color = Hsv::new(hue, 1.0, 1.0);
         let new_color: Srgb = hsv_color
-            .shift_hue((iter as f32 * (360.0 / length as f32)) as f32)
+            .shift_hue(iter as f32 * (360.0 / length as f32))
             .into();
         u32::from_be_bytes([
             0xff,
@@ -31,5 +73,7 @@ fn apply_palette(iter: u64, stable: bool, length: u32, hue: f32) -> u32 {
             (new_color.green * 255.0) as u8,
             (new_color.red * 255.0) as u8,
         ])
+    } else {
+        0xff000000
     }
 }
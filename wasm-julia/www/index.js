@@ -1,15 +1,21 @@
-import * as wasm from "wasm-julia";
+import { Renderer } from "wasm-julia";
+import { memory } from "wasm-julia/wasm_julia_bg";
+
+const SIZE = 1000;
+const renderer = Renderer.new();
 
 function run() {
-    var data = wasm.gen(
+    renderer.gen(
         document.getElementById("palette-length").value,
         document.getElementById("palette-hue").value,
         document.getElementById("cx").value,
-        document.getElementById("cy").value
+        document.getElementById("cy").value,
+        document.getElementById("max-iterations").value
     );
     var c = document.getElementById("myCanvas");
     var ctx = c.getContext("2d");
-    var iData = new ImageData(new Uint8ClampedArray(data.buffer), 1000, 1000);
+    var data = new Uint8ClampedArray(memory.buffer, renderer.pixels(), SIZE * SIZE * 4);
+    var iData = new ImageData(data, SIZE, SIZE);
     ctx.putImageData(iData, 0, 0);
 }
 